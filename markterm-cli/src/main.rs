@@ -1,37 +1,96 @@
 use clap::Parser;
+use std::io::BufRead;
 use std::path::PathBuf;
 
 #[derive(Parser)]
 #[command(about, long_about = None)]
 #[derive(Debug)]
 struct Args {
-    // Path to the file
+    // Path to the file, or `-` to render markdown from stdin as it arrives
     file_path: String,
 
     #[arg(short, long, default_value_t = clap::ColorChoice::Auto)]
     color: clap::ColorChoice,
+
+    /// Path to a TOML, JSON or YAML theme file
+    #[arg(short, long)]
+    theme: Option<PathBuf>,
+
+    /// Number of colors the terminal can display. Detected from COLORTERM and TERM by default
+    #[arg(long, value_enum)]
+    color_depth: Option<ColorDepth>,
+}
+
+#[derive(Clone, Copy, Debug, clap::ValueEnum)]
+enum ColorDepth {
+    Truecolor,
+    #[value(name = "256")]
+    Ansi256,
+    #[value(name = "16")]
+    Ansi16,
 }
 
 fn main() {
     let args = Args::parse();
 
+    let color_choice = match args.color {
+        clap::ColorChoice::Always => markterm::ColorChoice::Always,
+        clap::ColorChoice::Auto => markterm::ColorChoice::Auto,
+        clap::ColorChoice::Never => markterm::ColorChoice::Never,
+    };
+
+    let mut builder = markterm::Renderer::builder().color_choice(color_choice);
+    if let Some(color_depth) = args.color_depth {
+        builder = builder.color_depth(match color_depth {
+            ColorDepth::Truecolor => markterm::ColorDepth::TrueColor,
+            ColorDepth::Ansi256 => markterm::ColorDepth::Ansi256,
+            ColorDepth::Ansi16 => markterm::ColorDepth::Ansi16,
+        });
+    }
+
+    if let Some(theme_path) = &args.theme {
+        match markterm::Theme::from_path(theme_path) {
+            Ok(theme) => builder = builder.theme(theme),
+            Err(err) => {
+                eprintln!("Failed to load theme: {err}");
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let renderer = builder.build();
+
+    let result = if args.file_path == "-" {
+        render_stdin(&renderer)
+    } else {
+        render_file(&renderer, &args.file_path)
+    };
+
+    if let Err(err) = result {
+        eprintln!("Failed to render markdown: {err}");
+        std::process::exit(1);
+    }
+}
+
+fn render_file(renderer: &markterm::Renderer, path: &str) -> Result<(), markterm::Error> {
     let mut file_path = PathBuf::new();
-    file_path.push(&args.file_path);
+    file_path.push(path);
 
     if !file_path.exists() {
         println!("File not found");
-        return;
+        return Ok(());
     }
 
-    let color_choice = match args.color {
-        clap::ColorChoice::Always => markterm::ColorChoice::Always,
-        clap::ColorChoice::Auto => markterm::ColorChoice::Auto,
-        clap::ColorChoice::Never => markterm::ColorChoice::Never,
-    };
+    renderer.render_path(&file_path, &mut std::io::stdout().lock())
+}
 
-    let result = markterm::render_file_to_stdout(&file_path, None, color_choice);
-    match result {
-        Ok(()) => (),
-        Err(err) => panic!("Failed to render markdown {err}"),
+/// Renders every line from stdin as soon as the block it belongs to is complete.
+fn render_stdin(renderer: &markterm::Renderer) -> Result<(), markterm::Error> {
+    let mut stream = renderer.stream(std::io::stdout().lock());
+    for line in std::io::stdin().lock().lines() {
+        stream.push(&line?)?;
+        stream.push("\n")?;
     }
+
+    stream.finish().map(|_| ())
 }
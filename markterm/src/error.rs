@@ -0,0 +1,113 @@
+use std::fmt;
+
+/// The errors that can occur while rendering markdown or building a theme.
+#[derive(Debug)]
+pub enum Error {
+    /// The markdown could not be read, or the rendered markdown could not be written.
+    Io(std::io::Error),
+
+    /// The markdown that was read is not valid UTF-8.
+    InvalidUtf8(std::str::Utf8Error),
+
+    /// The markdown could not be parsed.
+    Parse {
+        /// The reason the markdown could not be parsed.
+        reason: String,
+
+        /// Where in the markdown the error was found, if it is known.
+        position: Option<Position>,
+    },
+
+    /// The color is not written in any of the notations that [`crate::Color`] accepts.
+    /// Contains the invalid color.
+    InvalidColor(String),
+
+    /// The theme file could not be read into a theme.
+    InvalidTheme {
+        /// The path to the key that is not valid. e.g. `header_1.fg`.
+        key: Option<String>,
+
+        /// The reason the theme is not valid.
+        reason: String,
+    },
+}
+
+/// A position in the markdown source.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Position {
+    /// The line number, starting at 1.
+    pub line: usize,
+
+    /// The column number, starting at 1.
+    pub column: usize,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "{e}"),
+            Error::InvalidUtf8(e) => write!(f, "Markdown is not valid UTF-8: {e}"),
+            Error::Parse {
+                reason,
+                position: Some(position),
+            } => write!(
+                f,
+                "Unable to parse markdown at {}:{}: {reason}",
+                position.line, position.column
+            ),
+            Error::Parse {
+                reason,
+                position: None,
+            } => write!(f, "Unable to parse markdown: {reason}"),
+            Error::InvalidColor(color) => write!(f, "Invalid color `{color}`"),
+            Error::InvalidTheme {
+                key: Some(key),
+                reason,
+            } => write!(f, "Invalid theme at `{key}`: {reason}"),
+            Error::InvalidTheme { key: None, reason } => write!(f, "Invalid theme: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::InvalidUtf8(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(value: std::io::Error) -> Self {
+        Error::Io(value)
+    }
+}
+
+impl From<std::str::Utf8Error> for Error {
+    fn from(value: std::str::Utf8Error) -> Self {
+        Error::InvalidUtf8(value)
+    }
+}
+
+impl From<markdown::message::Message> for Error {
+    fn from(value: markdown::message::Message) -> Self {
+        let position = value.place.map(|place| {
+            let point = match *place {
+                markdown::message::Place::Position(position) => position.start,
+                markdown::message::Place::Point(point) => point,
+            };
+
+            Position {
+                line: point.line,
+                column: point.column,
+            }
+        });
+
+        Error::Parse {
+            reason: value.reason,
+            position,
+        }
+    }
+}
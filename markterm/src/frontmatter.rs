@@ -0,0 +1,541 @@
+use markdown::mdast;
+
+/// The formats that the frontmatter of a document can be written in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FrontmatterFormat {
+    /// YAML, fenced with `---`.
+    Yaml,
+
+    /// TOML, fenced with `+++`.
+    Toml,
+}
+
+/// The frontmatter at the top of a markdown document.
+///
+/// Lists are joined with `, `, and the fields of nested mappings and tables are read as
+/// fields whose keys are joined with `.`, such as `author.name`. Frontmatter that is not
+/// valid has no fields.
+///
+/// Without the `yaml` and `toml` features, only a subset of each format is read: the
+/// `key: value` fields at the top level of YAML, with lists and block scalars as their
+/// values but without nested mappings, and the `key = value` fields of TOML and of its
+/// tables, without arrays of tables.
+///
+/// ### Example
+/// ```rust
+/// use markterm::Frontmatter;
+///
+/// let frontmatter = Frontmatter::parse("---\ntitle: Hello\ntags: [a, b]\n---\n# Text").unwrap();
+/// assert_eq!(frontmatter.title(), Some("Hello"));
+/// assert_eq!(frontmatter.get("tags"), Some("a, b"));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frontmatter {
+    /// The format that the frontmatter is written in.
+    pub format: FrontmatterFormat,
+
+    /// The frontmatter as it is written, without its fences.
+    pub raw: String,
+
+    /// The fields of the frontmatter in the order they are written.
+    pub fields: Vec<(String, String)>,
+}
+
+impl Frontmatter {
+    /// Parses the frontmatter at the top of the markdown. Returns `None` if the markdown
+    /// does not start with frontmatter.
+    pub fn parse(markdown: &str) -> Option<Self> {
+        let ast = markdown::to_mdast(markdown, &crate::writer::default_parse_options()).ok()?;
+        Self::from_node(ast.children()?.first()?)
+    }
+
+    /// Gets the value of the field with the key.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        (self.fields.iter())
+            .find(|(field, _)| field == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Gets the title of the document.
+    pub fn title(&self) -> Option<&str> {
+        self.get("title")
+    }
+
+    /// Reads the frontmatter of a YAML or TOML node.
+    pub(crate) fn from_node(node: &mdast::Node) -> Option<Self> {
+        let (format, raw) = match node {
+            mdast::Node::Yaml(yaml) => (FrontmatterFormat::Yaml, &yaml.value),
+            mdast::Node::Toml(toml) => (FrontmatterFormat::Toml, &toml.value),
+            _ => return None,
+        };
+
+        let fields = match format {
+            FrontmatterFormat::Yaml => yaml_fields(raw),
+            FrontmatterFormat::Toml => toml_fields(raw),
+        };
+
+        Some(Frontmatter {
+            format,
+            raw: raw.clone(),
+            fields,
+        })
+    }
+}
+
+/// Reads the fields of YAML, with the keys of nested mappings joined with `.`.
+#[cfg(feature = "yaml")]
+fn yaml_fields(yaml: &str) -> Vec<(String, String)> {
+    let mut fields = Vec::new();
+    if let Ok(serde_yaml::Value::Mapping(mapping)) = serde_yaml::from_str(yaml) {
+        push_yaml_fields("", &mapping, &mut fields);
+    }
+
+    fields
+}
+
+/// Adds the fields of a YAML mapping, with their keys after the prefix.
+#[cfg(feature = "yaml")]
+fn push_yaml_fields(
+    prefix: &str,
+    mapping: &serde_yaml::Mapping,
+    fields: &mut Vec<(String, String)>,
+) {
+    for (key, value) in mapping {
+        let key = field_key(prefix, &yaml_value(key));
+        match value {
+            serde_yaml::Value::Mapping(mapping) => push_yaml_fields(&key, mapping, fields),
+            value => fields.push((key, yaml_value(value))),
+        }
+    }
+}
+
+/// Writes a YAML value as text. The items of lists are joined with `, `, and mappings in
+/// lists are left out.
+#[cfg(feature = "yaml")]
+fn yaml_value(value: &serde_yaml::Value) -> String {
+    match value {
+        serde_yaml::Value::Null | serde_yaml::Value::Mapping(_) => String::new(),
+        serde_yaml::Value::Bool(value) => value.to_string(),
+        serde_yaml::Value::Number(value) => value.to_string(),
+        serde_yaml::Value::String(value) => value.trim_end_matches('\n').to_string(),
+        serde_yaml::Value::Sequence(items) => join_items(items.iter().map(yaml_value)),
+        serde_yaml::Value::Tagged(tagged) => yaml_value(&tagged.value),
+    }
+}
+
+/// Reads the fields of TOML, with the keys of the fields in tables joined with `.`.
+#[cfg(feature = "toml")]
+fn toml_fields(toml: &str) -> Vec<(String, String)> {
+    let mut fields = Vec::new();
+    if let Ok(table) = toml.parse::<toml::Table>() {
+        push_toml_fields("", &table, &mut fields);
+    }
+
+    fields
+}
+
+/// Adds the fields of a TOML table, with their keys after the prefix.
+#[cfg(feature = "toml")]
+fn push_toml_fields(prefix: &str, table: &toml::Table, fields: &mut Vec<(String, String)>) {
+    for (key, value) in table {
+        let key = field_key(prefix, key);
+        match value {
+            toml::Value::Table(table) => push_toml_fields(&key, table, fields),
+            value => fields.push((key, toml_value(value))),
+        }
+    }
+}
+
+/// Writes a TOML value as text. The items of arrays are joined with `, `, and tables in
+/// arrays are left out.
+#[cfg(feature = "toml")]
+fn toml_value(value: &toml::Value) -> String {
+    match value {
+        toml::Value::String(value) => value.clone(),
+        toml::Value::Integer(value) => value.to_string(),
+        toml::Value::Float(value) => value.to_string(),
+        toml::Value::Boolean(value) => value.to_string(),
+        toml::Value::Datetime(value) => value.to_string(),
+        toml::Value::Array(items) => join_items(items.iter().map(toml_value)),
+        toml::Value::Table(_) => String::new(),
+    }
+}
+
+/// Gets the key of a field in a nested mapping or table.
+#[cfg(any(feature = "yaml", feature = "toml"))]
+fn field_key(prefix: &str, key: &str) -> String {
+    match prefix {
+        "" => key.to_string(),
+        prefix => format!("{prefix}.{key}"),
+    }
+}
+
+/// Joins the items of a list with `, `, leaving out the empty ones.
+fn join_items(items: impl Iterator<Item = String>) -> String {
+    (items.filter(|item| !item.is_empty()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// A field at the top level of YAML that is read without the `yaml` feature.
+#[cfg(not(feature = "yaml"))]
+struct YamlField {
+    key: String,
+    values: Vec<String>,
+
+    /// The style of the block scalar of the field, which is `|` or `>`.
+    style: Option<char>,
+
+    /// Whether the value is a nested mapping, which is left out.
+    is_mapping: bool,
+}
+
+/// Reads the fields at the top level of YAML without the `yaml` feature. The indented
+/// lines under a key are its value when they are the items of a list or the lines of a
+/// block scalar. Fields whose values are nested mappings are left out.
+#[cfg(not(feature = "yaml"))]
+fn yaml_fields(yaml: &str) -> Vec<(String, String)> {
+    let mut fields: Vec<YamlField> = Vec::new();
+    for line in yaml.lines() {
+        let trimmed = line.trim();
+        let block = fields.last_mut().filter(|field| field.style.is_some());
+
+        // Blank lines are kept in block scalars, as they separate paragraphs.
+        if trimmed.is_empty() {
+            if let Some(field) = block {
+                field.values.push(String::new());
+            }
+
+            continue;
+        }
+
+        if line.starts_with(char::is_whitespace) {
+            if let Some(field) = block {
+                field.values.push(trimmed.to_string());
+                continue;
+            }
+        }
+
+        if trimmed.starts_with('#') {
+            continue;
+        }
+
+        if let Some(item) = trimmed.strip_prefix("- ") {
+            if let Some(field) = fields.last_mut() {
+                field.values.push(scalar(strip_comment(item)));
+            }
+
+            continue;
+        }
+
+        if line.starts_with(char::is_whitespace) {
+            if let Some(field) = fields.last_mut() {
+                field.is_mapping = true;
+            }
+
+            continue;
+        }
+
+        if let Some((key, value)) = trimmed.split_once(':') {
+            let (values, style) = match strip_comment(value).trim() {
+                "" => (Vec::new(), None),
+                value if value.starts_with(['|', '>']) => (Vec::new(), value.chars().next()),
+                value => (vec![scalar(value)], None),
+            };
+
+            fields.push(YamlField {
+                key: scalar(key),
+                values,
+                style,
+                is_mapping: false,
+            });
+        }
+    }
+
+    (fields.into_iter())
+        .filter(|field| !field.is_mapping)
+        .map(|field| (field.key, join_values(field.values, field.style)))
+        .collect()
+}
+
+/// Joins the values of a field. The lines of a literal block scalar (`|`) are kept, the
+/// lines of a folded one (`>`) are joined with spaces except at blank lines, and the
+/// items of lists are joined with `, `.
+#[cfg(not(feature = "yaml"))]
+fn join_values(mut values: Vec<String>, style: Option<char>) -> String {
+    while values.last().is_some_and(String::is_empty) {
+        values.pop();
+    }
+
+    match style {
+        Some('|') => values.join("\n"),
+        Some(_) => values
+            .split(String::is_empty)
+            .map(|paragraph| paragraph.join(" "))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        None => join_items(values.into_iter()),
+    }
+}
+
+/// Reads the fields of TOML without the `toml` feature. The keys of the fields in tables
+/// start with the name of the table, and arrays of tables are left out.
+#[cfg(not(feature = "toml"))]
+fn toml_fields(toml: &str) -> Vec<(String, String)> {
+    let mut fields = Vec::new();
+    let mut table = Some(String::new());
+    let mut lines = toml.lines();
+    while let Some(line) = lines.next() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if line.starts_with("[[") {
+            table = None;
+            continue;
+        }
+
+        if let Some(name) =
+            (line.strip_prefix('[')).and_then(|line| strip_comment(line).strip_suffix(']'))
+        {
+            table = Some(name.trim().to_string());
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+
+        // Multi-line strings and arrays run until the line that closes them.
+        let mut value = strip_comment(value).trim().to_string();
+        let close = match ["\"\"\"", "'''"]
+            .into_iter()
+            .find(|quotes| value.starts_with(quotes))
+        {
+            Some(quotes) if value.len() < 6 || !value.ends_with(quotes) => Some(quotes),
+            Some(_) => None,
+            None if value.starts_with('[') && !value.ends_with(']') => Some("]"),
+            None => None,
+        };
+
+        if let Some(close) = close {
+            for line in lines.by_ref() {
+                let line = match close {
+                    "]" => strip_comment(line),
+                    _ => line,
+                };
+
+                value.push('\n');
+                value.push_str(line);
+                if line.trim_end().ends_with(close) {
+                    break;
+                }
+            }
+        }
+
+        match table.as_deref() {
+            Some("") => fields.push((scalar(key), scalar(&value))),
+            Some(table) => fields.push((format!("{table}.{}", scalar(key)), scalar(&value))),
+            None => {}
+        }
+    }
+
+    fields
+}
+
+/// Takes the comment off the end of a value. A `#` only starts a comment after a space
+/// and outside of quotes, so that values such as `C#` are kept.
+#[cfg(not(all(feature = "yaml", feature = "toml")))]
+fn strip_comment(value: &str) -> &str {
+    let mut quote = None;
+    let mut previous = ' ';
+    for (index, char) in value.char_indices() {
+        match quote {
+            Some(quote_char) if char == quote_char => quote = None,
+            Some(_) => {}
+            None if matches!(char, '"' | '\'') && matches!(previous, ' ' | '\t' | '[' | ',') => {
+                quote = Some(char)
+            }
+            None if char == '#' && previous.is_whitespace() => return value[..index].trim_end(),
+            None => {}
+        }
+
+        previous = char;
+    }
+
+    value.trim_end()
+}
+
+/// Reads a value, without its quotes. The items of inline lists are joined with `, `.
+#[cfg(not(all(feature = "yaml", feature = "toml")))]
+fn scalar(value: &str) -> String {
+    let value = value.trim();
+    if let Some(items) = value
+        .strip_prefix('[')
+        .and_then(|value| value.strip_suffix(']'))
+    {
+        return join_items(split_items(items).into_iter().map(scalar));
+    }
+
+    // The newline right after the quotes that open a multi-line string is not part of it.
+    for quotes in ["\"\"\"", "'''"] {
+        if let Some(value) =
+            (value.strip_prefix(quotes)).and_then(|value| value.strip_suffix(quotes))
+        {
+            return value.strip_prefix('\n').unwrap_or(value).to_string();
+        }
+    }
+
+    for quote in ['"', '\''] {
+        if let Some(value) = value
+            .strip_prefix(quote)
+            .and_then(|value| value.strip_suffix(quote))
+        {
+            return value.to_string();
+        }
+    }
+
+    value.to_string()
+}
+
+/// Splits the items of an inline list at the commas that are not in quotes.
+#[cfg(not(all(feature = "yaml", feature = "toml")))]
+fn split_items(items: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut quote = None;
+    let mut start = 0;
+    for (index, char) in items.char_indices() {
+        match quote {
+            Some(quote_char) if char == quote_char => quote = None,
+            Some(_) => {}
+            None if matches!(char, '"' | '\'') && items[start..index].trim().is_empty() => {
+                quote = Some(char)
+            }
+            None if char == ',' => {
+                parts.push(&items[start..index]);
+                start = index + 1;
+            }
+            None => {}
+        }
+    }
+
+    parts.push(&items[start..]);
+    parts
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_read_yaml_fields() {
+        let markdown =
+            "---\ntitle: \"Hello: world\"\ntags:\n  - a\n  - 'b'\ndraft: false\n---\nText";
+        let frontmatter = Frontmatter::parse(markdown).unwrap();
+
+        assert_eq!(frontmatter.format, FrontmatterFormat::Yaml);
+        assert_eq!(
+            frontmatter.fields,
+            vec![
+                ("title".to_string(), "Hello: world".to_string()),
+                ("tags".to_string(), "a, b".to_string()),
+                ("draft".to_string(), "false".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_keep_lines_of_block_scalars() {
+        let markdown = "---\nliteral: |\n  one\n  two\n\n  three\nfolded: >-\n  one\n  two\n\n  three\n\ntags:\n  - a\n---";
+        let frontmatter = Frontmatter::parse(markdown).unwrap();
+
+        assert_eq!(frontmatter.get("literal"), Some("one\ntwo\n\nthree"));
+        assert_eq!(frontmatter.get("folded"), Some("one two\nthree"));
+        assert_eq!(frontmatter.get("tags"), Some("a"));
+    }
+
+    #[test]
+    fn should_read_toml_fields() {
+        let markdown =
+            "+++\ntitle = 'Hello'\ntags = [\"a\", \"b\"]\n\n[author]\nname = \"Me\"\n+++";
+        let frontmatter = Frontmatter::parse(markdown).unwrap();
+
+        assert_eq!(frontmatter.format, FrontmatterFormat::Toml);
+        assert_eq!(frontmatter.title(), Some("Hello"));
+        assert_eq!(frontmatter.get("tags"), Some("a, b"));
+        assert_eq!(frontmatter.get("author.name"), Some("Me"));
+    }
+
+    #[test]
+    fn should_leave_out_comments() {
+        let yaml = "---\n# The title\ntitle: Hello # greeting\nlanguage: C#\ntags:\n  - a # first\n  - '#b'\n---";
+        let frontmatter = Frontmatter::parse(yaml).unwrap();
+
+        assert_eq!(frontmatter.title(), Some("Hello"));
+        assert_eq!(frontmatter.get("language"), Some("C#"));
+        assert_eq!(frontmatter.get("tags"), Some("a, #b"));
+
+        let toml = "+++\ntitle = \"Hash # not a comment\" # comment\n+++";
+        let frontmatter = Frontmatter::parse(toml).unwrap();
+
+        assert_eq!(frontmatter.title(), Some("Hash # not a comment"));
+    }
+
+    #[test]
+    fn should_not_split_quoted_commas() {
+        let yaml = "---\nauthors: [\"Smith, J.\", 'Doe, A.']\ntitle: \"One, two\"\n---";
+        let frontmatter = Frontmatter::parse(yaml).unwrap();
+
+        assert_eq!(frontmatter.get("authors"), Some("Smith, J., Doe, A."));
+        assert_eq!(frontmatter.title(), Some("One, two"));
+
+        let toml = "+++\nauthors = [\"Smith, J.\"]\n+++";
+        let frontmatter = Frontmatter::parse(toml).unwrap();
+
+        assert_eq!(frontmatter.get("authors"), Some("Smith, J."));
+    }
+
+    #[test]
+    fn should_read_multi_line_toml_strings() {
+        let markdown = "+++\ntitle = \"\"\"\nHello\nworld\"\"\"\ntags = [\n  \"a\", # first\n  \"b\",\n]\ndraft = true\n+++";
+        let frontmatter = Frontmatter::parse(markdown).unwrap();
+
+        assert_eq!(frontmatter.title(), Some("Hello\nworld"));
+        assert_eq!(frontmatter.get("tags"), Some("a, b"));
+        assert_eq!(frontmatter.get("draft"), Some("true"));
+    }
+
+    #[test]
+    #[cfg(feature = "yaml")]
+    fn should_join_keys_of_nested_mappings() {
+        let markdown = "---\nauthor:\n  name: Me\n  links:\n    site: a.com\ntitle: Hi\n---";
+        let frontmatter = Frontmatter::parse(markdown).unwrap();
+
+        assert_eq!(
+            frontmatter.fields,
+            vec![
+                ("author.name".to_string(), "Me".to_string()),
+                ("author.links.site".to_string(), "a.com".to_string()),
+                ("title".to_string(), "Hi".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "yaml"))]
+    fn should_leave_out_nested_mappings() {
+        let markdown = "---\nauthor:\n  name: Me\n  links:\n    site: a.com\ntitle: Hi\n---";
+        let frontmatter = Frontmatter::parse(markdown).unwrap();
+
+        assert_eq!(
+            frontmatter.fields,
+            vec![("title".to_string(), "Hi".to_string())]
+        );
+    }
+
+    #[test]
+    fn should_not_parse_documents_without_frontmatter() {
+        assert_eq!(Frontmatter::parse("# Title\n\n---\n\nText"), None);
+    }
+}
@@ -3,7 +3,23 @@
 //! A cross-platform library to render colored markdown to the terminal.
 //! The rendered markdown is colored and is themeable.
 //!
-//! The module exposes 4 functions that for handling markdown
+//! Markdown is rendered with a [`Renderer`], which is built once with the theme, colors,
+//! width and parse options and can then render any number of documents.
+//!
+//! ```rust
+//! let renderer = markterm::Renderer::builder()
+//!     .color_choice(markterm::ColorChoice::Never)
+//!     .width(Some(20))
+//!     .build();
+//!
+//! let text = renderer
+//!     .render_to_string("> This is a long blockquote that will be wrapped")
+//!     .unwrap();
+//! assert_eq!(text, "│ This is a long\n│ blockquote that\n│ will be wrapped\n");
+//! ```
+//!
+//! The module also exposes 4 functions that detect the theme and the width of the
+//! terminal every time they are called:
 //! * [`render_file_to_stdout`][]
 //!   - Renders the passed in file to stdout using the theme.
 //! * [`render_file`]
@@ -15,16 +31,8 @@
 //!
 //! ## Status
 //! This project started out as a way for me to learn rust. It's gone beyond that now.
-//! At this point, markterm is not compatible with inline html and tables. It also does not support multi level indentations.
-//! These features are in the works
-//!
-//! ## Roadmap
-//! There is a lot we want to do to markterm. The items we have in our immediate queue are listed
-//! below.
-//! - Add support for nested lists.
-//! - Add support for generic colors rather than always having to use RGB.
-//! - Add support for tables.
-//! - Add support for inline html.
+//! HTML is supported for a small set of tags, such as `<kbd>`, `<br>`, `<sub>`, `<sup>`,
+//! `<details>` and `<img>`. Other tags are handled as set by [`UnknownHtml`].
 //!
 //! ## Credits
 //! This project would not be possible without [markdown-rs](https://github.com/wooorm/markdown-rs).
@@ -33,11 +41,34 @@
 /// Modules to help theme the output
 pub mod themes;
 
-pub use themes::{color::Color, get_default_theme, ElementTheme, TextStyle, Theme};
+/// The errors returned by markterm.
+mod error;
+
+/// Reads the frontmatter at the top of documents.
+mod frontmatter;
+
+pub use error::{Error, Position};
+pub use frontmatter::{Frontmatter, FrontmatterFormat};
+
+pub use themes::{
+    color::{Color, ColorDepth},
+    get_default_theme, ElementTheme, HeadingFormat, HeadingPrefix, NumberFormat, NumberStyle,
+    SyntaxTheme, TextStyle, Theme,
+};
+
+#[cfg(feature = "serde")]
+pub use themes::ThemeFormat;
 
 /// A module to write the appropriate terminal escape sequence to color the text
 mod writer;
 
+/// The reusable renderer and its builder.
+mod renderer;
+
+pub use markdown::ParseOptions;
+use renderer::RenderOptions;
+pub use renderer::{Renderer, RendererBuilder, StreamRenderer};
+
 use std::io::{IsTerminal, Read};
 use std::{
     fs::File,
@@ -58,6 +89,114 @@ pub enum ColorChoice {
     Never,
 }
 
+/// The graphics protocol used to draw images that are stored in local files.
+///
+/// Images are only drawn when the output is colored. Images that cannot be drawn are
+/// written as a `[image: alt text](url)` placeholder instead.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImageProtocol {
+    /// The [Kitty graphics protocol](https://sw.kovidgoyal.net/kitty/graphics-protocol/).
+    /// Images that are not PNGs need the `images` feature.
+    Kitty,
+
+    /// The inline image protocol of iTerm2, which is also supported by WezTerm.
+    Iterm2,
+
+    /// Sixel graphics. Needs the `images` feature.
+    Sixel,
+
+    /// Writes a placeholder for every image.
+    Placeholder,
+}
+
+impl ImageProtocol {
+    /// Detects the protocol supported by the terminal from the environment variables
+    /// it sets. Returns [`ImageProtocol::Placeholder`] if the terminal is not known.
+    pub fn detect() -> Self {
+        let term = std::env::var("TERM").unwrap_or_default();
+        let term_program = std::env::var("TERM_PROGRAM").unwrap_or_default();
+
+        if std::env::var_os("KITTY_WINDOW_ID").is_some()
+            || term == "xterm-kitty"
+            || term_program == "ghostty"
+        {
+            ImageProtocol::Kitty
+        } else if term_program == "iTerm.app" || term_program == "WezTerm" {
+            ImageProtocol::Iterm2
+        } else if term.contains("sixel") || term.starts_with("foot") || term == "mlterm" {
+            ImageProtocol::Sixel
+        } else {
+            ImageProtocol::Placeholder
+        }
+    }
+}
+
+/// Where the definitions of footnotes are written.
+///
+/// The markers of footnotes are not hyperlinks to their definitions. A terminal opens a
+/// hyperlink such as `file:///doc.md#fn-1` in another program instead of scrolling to the
+/// definition in its output, and output that is piped or streamed has no file to link to.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum FootnotePlacement {
+    /// Writes the definitions in a "Footnotes" section at the end of the document, in the
+    /// order that the footnotes are referenced.
+    #[default]
+    End,
+
+    /// Writes every definition where it is defined.
+    Definition,
+}
+
+/// How links are written when they cannot be written as terminal hyperlinks, because
+/// the output is not colored or hyperlinks are disabled.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum LinkFallback {
+    /// Writes the URL and the title after the text of the link. i.e `text (url "title")`
+    #[default]
+    Inline,
+
+    /// Writes a number after the text of the link, and the URL and the title of every
+    /// number at the end of the document. i.e `text[1]`
+    Numbered,
+
+    /// Writes only the text of the link.
+    TextOnly,
+}
+
+/// How HTML tags that markterm does not know are written.
+///
+/// Known tags such as `<kbd>`, `<br>` and `<img>` are styled, and comments are always
+/// left out.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum UnknownHtml {
+    /// Leaves the tags out, but writes the text between them. `<script>` and `<style>`
+    /// elements are left out with their content.
+    #[default]
+    Strip,
+
+    /// Writes the tags as they are.
+    Raw,
+
+    /// Writes the tags with the `html_tag` theme, which is dimmed by default.
+    Dimmed,
+}
+
+/// How the YAML or TOML frontmatter at the top of a document is written.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum FrontmatterDisplay {
+    /// Leaves the frontmatter out.
+    #[default]
+    Hide,
+
+    /// Writes the fields of the frontmatter as a table of keys and values with the
+    /// `frontmatter` theme.
+    Table,
+
+    /// Writes the `title` field as a banner across the top of the document with the
+    /// `frontmatter_banner` theme.
+    Banner,
+}
+
 /// Renders the contents of the passed in file to stdout.
 ///
 /// ### Example
@@ -72,7 +211,7 @@ pub fn render_file_to_stdout(
     file_path: &PathBuf,
     theme: Option<&self::Theme>,
     color_choice: ColorChoice,
-) -> Result<(), std::io::Error> {
+) -> Result<(), Error> {
     let mut stdout = std::io::stdout().lock();
     let should_colorize = match color_choice {
         ColorChoice::Always => true,
@@ -85,6 +224,8 @@ pub fn render_file_to_stdout(
 
 /// Renders the contents of the passed in file to any implementation of std::io::Write.
 ///
+/// Returns an error if the file cannot be read or is not valid UTF-8.
+///
 /// ### Example
 /// ```rust
 /// use std::io::Write;
@@ -93,28 +234,28 @@ pub fn render_file_to_stdout(
 /// path.push("benches/sample.md");
 ///
 /// let mut dest = Vec::new();
-/// markterm::render_file(&path, None, &mut dest, false);
+/// markterm::render_file(&path, None, &mut dest, false).unwrap();
+///
+/// path.push("does-not-exist.md");
+/// let result = markterm::render_file(&path, None, &mut dest, false);
+/// assert!(matches!(result, Err(markterm::Error::Io(_))));
 /// ```
 pub fn render_file(
     file_path: &PathBuf,
     theme: Option<&Theme>,
     writer: &mut impl std::io::Write,
     should_colorize: bool,
-) -> Result<(), std::io::Error> {
-    let file = match File::open(file_path) {
-        Ok(f) => f,
-        Err(e) => {
-            panic!("Unable to open file: {e}");
-        }
-    };
+) -> Result<(), Error> {
+    let mut file_contents = Vec::new();
+    io::BufReader::new(File::open(file_path)?).read_to_end(&mut file_contents)?;
+    let file_contents = std::str::from_utf8(&file_contents)?;
 
-    let mut file_contents = String::new();
-
-    let _files = io::BufReader::new(file)
-        .read_to_string(&mut file_contents)
-        .unwrap();
+    let options = RenderOptions {
+        base_dir: file_path.parent().map(|dir| dir.to_path_buf()),
+        ..Default::default()
+    };
 
-    render_text(&file_contents, theme, writer, should_colorize)
+    render_text_with_options(file_contents, theme, &options, writer, should_colorize)
 }
 
 /// Renders the contents of the passed in string to stdout.
@@ -129,7 +270,7 @@ pub fn render_text_to_stdout(
     text: &str,
     theme: Option<&Theme>,
     color_choice: ColorChoice,
-) -> Result<(), std::io::Error> {
+) -> Result<(), Error> {
     let mut stdout = std::io::stdout().lock();
 
     let should_colorize = match color_choice {
@@ -157,12 +298,56 @@ pub fn render_text(
     theme: Option<&Theme>,
     writer: &mut impl std::io::Write,
     should_colorize: bool,
-) -> Result<(), std::io::Error> {
-    let default_theme = get_default_theme();
+) -> Result<(), Error> {
+    render_text_with_options(
+        text,
+        theme,
+        &RenderOptions::default(),
+        writer,
+        should_colorize,
+    )
+}
+
+fn render_text_with_options(
+    text: &str,
+    theme: Option<&Theme>,
+    options: &RenderOptions,
+    writer: &mut impl std::io::Write,
+    should_colorize: bool,
+) -> Result<(), Error> {
+    let default_theme;
     let theme = match theme {
         Some(x) => x,
-        None => &default_theme,
+        None => {
+            default_theme = get_default_theme();
+            &default_theme
+        }
     };
 
-    writer::write(text, theme, writer, should_colorize)
+    writer::write_with_options(
+        text,
+        theme,
+        options,
+        &writer::default_parse_options(),
+        writer,
+        should_colorize,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_return_error_if_file_is_not_utf8() {
+        let mut path = std::env::temp_dir();
+        path.push("markterm-invalid-utf8.md");
+        std::fs::write(&path, [b'#', b' ', 0xFF, 0xFE]).unwrap();
+
+        let mut dest = Vec::new();
+        let result = render_file(&path, None, &mut dest, false);
+        let _ = std::fs::remove_file(&path);
+
+        assert!(matches!(result, Err(Error::InvalidUtf8(_))));
+    }
 }
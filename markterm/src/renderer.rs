@@ -0,0 +1,449 @@
+use crate::{
+    get_default_theme, writer, ColorChoice, ColorDepth, Error, FootnotePlacement,
+    FrontmatterDisplay, ImageProtocol, LinkFallback, Theme, UnknownHtml,
+};
+use markdown::ParseOptions;
+use std::io::{IsTerminal, Read};
+use std::path::{Path, PathBuf};
+
+/// Renders markdown that arrives a chunk at a time.
+mod stream;
+
+pub use stream::StreamRenderer;
+
+/// The size in bytes of the largest image file that is drawn when none is set.
+const DEFAULT_MAX_IMAGE_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Options that change how the markdown is laid out.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct RenderOptions {
+    /// The number of columns that paragraphs, list items and block quotes are wrapped to.
+    /// Text is not wrapped when this is `None`.
+    pub width: Option<usize>,
+
+    /// Indicates whether links are written as terminal hyperlinks when the output is colored.
+    pub hyperlinks: bool,
+
+    /// The protocol used to draw images.
+    pub images: ImageProtocol,
+
+    /// The directory that the paths of images are relative to.
+    /// Paths are relative to the current directory when this is `None`.
+    pub base_dir: Option<PathBuf>,
+
+    /// The size in bytes of the largest image file that is drawn.
+    pub max_image_bytes: u64,
+
+    /// The number of colors that the terminal can display.
+    pub color_depth: ColorDepth,
+
+    /// Where the definitions of footnotes are written.
+    pub footnotes: FootnotePlacement,
+
+    /// Indicates whether rules, checkboxes, alerts, `<details>` summaries and math can be
+    /// written with characters that are not ASCII.
+    pub unicode: bool,
+
+    /// How links are written when they cannot be written as hyperlinks.
+    pub link_fallback: LinkFallback,
+
+    /// How HTML tags that are not known are written.
+    pub unknown_html: UnknownHtml,
+
+    /// How the frontmatter at the top of a document is written.
+    pub frontmatter: FrontmatterDisplay,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            width: terminal_width(),
+            hyperlinks: true,
+            images: ImageProtocol::detect(),
+            base_dir: None,
+            max_image_bytes: DEFAULT_MAX_IMAGE_BYTES,
+            color_depth: ColorDepth::detect(),
+            footnotes: FootnotePlacement::End,
+            unicode: is_unicode_locale(),
+            link_fallback: LinkFallback::Inline,
+            unknown_html: UnknownHtml::Strip,
+            frontmatter: FrontmatterDisplay::Hide,
+        }
+    }
+}
+
+/// Gets the width of the terminal, or `None` when stdout is not a terminal.
+fn terminal_width() -> Option<usize> {
+    terminal_size::terminal_size().map(|(width, _)| width.0 as usize)
+}
+
+/// Indicates whether the locale of the terminal uses UTF-8.
+///
+/// The locale is read from `LC_ALL`, `LC_CTYPE` and `LANG`, in that order. Terminals that
+/// do not set any of them, and Windows terminals, are expected to support Unicode.
+fn is_unicode_locale() -> bool {
+    if cfg!(windows) {
+        return true;
+    }
+
+    ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|locale| !locale.is_empty())
+        .is_none_or(|locale| {
+            let locale = locale.to_ascii_lowercase();
+            locale.contains("utf-8") || locale.contains("utf8")
+        })
+}
+
+/// Renders markdown with the same theme and options every time.
+///
+/// Building a renderer detects the theme, the colors and the width of the terminal once,
+/// so it is cheaper than the `render_*` functions when rendering many documents.
+///
+/// ### Example
+/// ```rust
+/// use markterm::{ColorChoice, Renderer};
+///
+/// let renderer = Renderer::builder()
+///     .color_choice(ColorChoice::Never)
+///     .width(Some(40))
+///     .build();
+///
+/// let text = renderer.render_to_string("> This is a `test`").unwrap();
+/// assert_eq!(text, "│ This is a  test \n");
+/// ```
+pub struct Renderer {
+    theme: Theme,
+    should_colorize: bool,
+    options: RenderOptions,
+    parse_options: ParseOptions,
+}
+
+impl Renderer {
+    /// Creates a renderer with the default theme and options.
+    pub fn new() -> Self {
+        Self::builder().build()
+    }
+
+    /// Creates a builder to configure a renderer.
+    pub fn builder() -> RendererBuilder {
+        RendererBuilder::default()
+    }
+
+    /// Gets the theme used by the renderer.
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    /// Renders the markdown in the string to the writer.
+    pub fn render_str(&self, text: &str, writer: &mut impl std::io::Write) -> Result<(), Error> {
+        writer::write_with_options(
+            text,
+            &self.theme,
+            &self.options,
+            &self.parse_options,
+            writer,
+            self.should_colorize,
+        )
+    }
+
+    /// Reads all of the markdown from the reader and renders it to the writer.
+    ///
+    /// Returns an error if the reader fails or the markdown is not valid UTF-8.
+    pub fn render_reader(
+        &self,
+        mut reader: impl Read,
+        writer: &mut impl std::io::Write,
+    ) -> Result<(), Error> {
+        let mut contents = Vec::new();
+        reader.read_to_end(&mut contents)?;
+
+        self.render_str(std::str::from_utf8(&contents)?, writer)
+    }
+
+    /// Renders the markdown in the file to the writer.
+    /// The paths of images are relative to the directory of the file, unless the
+    /// renderer was built with a [`RendererBuilder::base_dir`].
+    ///
+    /// Returns an error if the file cannot be read or is not valid UTF-8.
+    pub fn render_path(
+        &self,
+        path: impl AsRef<Path>,
+        writer: &mut impl std::io::Write,
+    ) -> Result<(), Error> {
+        let path = path.as_ref();
+        let contents = std::fs::read(path)?;
+        let options = RenderOptions {
+            base_dir: (self.options.base_dir.clone())
+                .or_else(|| path.parent().map(Path::to_path_buf)),
+            ..self.options.clone()
+        };
+
+        writer::write_with_options(
+            std::str::from_utf8(&contents)?,
+            &self.theme,
+            &options,
+            &self.parse_options,
+            writer,
+            self.should_colorize,
+        )
+    }
+
+    /// Creates a stream that renders markdown to the writer as it arrives.
+    ///
+    /// See [`StreamRenderer`] for when blocks are written.
+    pub fn stream<W: std::io::Write>(&self, writer: W) -> StreamRenderer<'_, W> {
+        StreamRenderer::new(self, writer)
+    }
+
+    /// Renders the markdown in the string and returns the rendered text.
+    pub fn render_to_string(&self, text: &str) -> Result<String, Error> {
+        let mut result = Vec::new();
+        self.render_str(text, &mut result)?;
+
+        // The rendered text is built from the markdown and the theme, which are both valid UTF-8.
+        Ok(String::from_utf8_lossy(&result).into_owned())
+    }
+}
+
+impl Default for Renderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Configures and builds a [`Renderer`].
+///
+/// Anything that is not set falls back to its default when the renderer is built.
+#[derive(Default)]
+pub struct RendererBuilder {
+    theme: Option<Theme>,
+    color_choice: Option<ColorChoice>,
+    width: Option<Option<usize>>,
+    hyperlinks: Option<bool>,
+    image_protocol: Option<ImageProtocol>,
+    base_dir: Option<PathBuf>,
+    max_image_bytes: Option<u64>,
+    color_depth: Option<ColorDepth>,
+    footnotes: Option<FootnotePlacement>,
+    unicode: Option<bool>,
+    link_fallback: Option<LinkFallback>,
+    unknown_html: Option<UnknownHtml>,
+    frontmatter: Option<FrontmatterDisplay>,
+    parse_options: Option<ParseOptions>,
+}
+
+impl RendererBuilder {
+    /// Sets the theme. Defaults to a theme that matches the background of the terminal.
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = Some(theme);
+        self
+    }
+
+    /// Sets whether the output is colored. Defaults to [`ColorChoice::Auto`], which
+    /// colors the output when stdout is a terminal.
+    pub fn color_choice(mut self, color_choice: ColorChoice) -> Self {
+        self.color_choice = Some(color_choice);
+        self
+    }
+
+    /// Sets the number of columns that text is wrapped to. `None` disables wrapping.
+    /// Defaults to the width of the terminal.
+    pub fn width(mut self, width: Option<usize>) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    /// Sets whether links are written as terminal hyperlinks when the output is colored.
+    /// Defaults to `true`.
+    pub fn hyperlinks(mut self, hyperlinks: bool) -> Self {
+        self.hyperlinks = Some(hyperlinks);
+        self
+    }
+
+    /// Sets the protocol used to draw images. Defaults to the protocol detected by
+    /// [`ImageProtocol::detect`].
+    pub fn image_protocol(mut self, protocol: ImageProtocol) -> Self {
+        self.image_protocol = Some(protocol);
+        self
+    }
+
+    /// Sets the directory that the paths of images are relative to. Defaults to the
+    /// current directory, or the directory of the file for [`Renderer::render_path`].
+    pub fn base_dir(mut self, base_dir: impl Into<PathBuf>) -> Self {
+        self.base_dir = Some(base_dir.into());
+        self
+    }
+
+    /// Sets the size in bytes of the largest image file that is drawn. Larger files are
+    /// written as a placeholder instead of being read. Defaults to 16 MiB.
+    pub fn max_image_bytes(mut self, max_image_bytes: u64) -> Self {
+        self.max_image_bytes = Some(max_image_bytes);
+        self
+    }
+
+    /// Sets the number of colors that the terminal can display. The colors of the theme
+    /// are reduced to the nearest colors of the palette when it has fewer colors.
+    /// Defaults to the depth detected by [`ColorDepth::detect`].
+    pub fn color_depth(mut self, color_depth: ColorDepth) -> Self {
+        self.color_depth = Some(color_depth);
+        self
+    }
+
+    /// Sets where the definitions of footnotes are written. Defaults to
+    /// [`FootnotePlacement::End`].
+    pub fn footnote_placement(mut self, placement: FootnotePlacement) -> Self {
+        self.footnotes = Some(placement);
+        self
+    }
+
+    /// Sets whether rules, checkboxes, alerts, `<details>` summaries and math can be written
+    /// with characters that are not ASCII. When they cannot, rules are drawn with `-`,
+    /// checkboxes are written as `[x]` and `[ ]`, alerts are drawn with ASCII borders and
+    /// no icon, and math is written as its TeX. Bullets, the bars of quotes and the borders
+    /// of tables are not changed. Defaults to whether the locale uses UTF-8.
+    pub fn unicode(mut self, unicode: bool) -> Self {
+        self.unicode = Some(unicode);
+        self
+    }
+
+    /// Sets how links are written when they cannot be written as hyperlinks. Defaults to
+    /// [`LinkFallback::Inline`].
+    pub fn link_fallback(mut self, fallback: LinkFallback) -> Self {
+        self.link_fallback = Some(fallback);
+        self
+    }
+
+    /// Sets how HTML tags that are not known are written. Defaults to
+    /// [`UnknownHtml::Strip`].
+    pub fn unknown_html(mut self, unknown_html: UnknownHtml) -> Self {
+        self.unknown_html = Some(unknown_html);
+        self
+    }
+
+    /// Sets how the frontmatter at the top of a document is written. Defaults to
+    /// [`FrontmatterDisplay::Hide`]. Use [`crate::Frontmatter::parse`] to read its fields.
+    pub fn frontmatter(mut self, frontmatter: FrontmatterDisplay) -> Self {
+        self.frontmatter = Some(frontmatter);
+        self
+    }
+
+    /// Sets the options used to parse the markdown. Defaults to GitHub flavored markdown
+    /// with frontmatter and math.
+    pub fn parse_options(mut self, parse_options: ParseOptions) -> Self {
+        self.parse_options = Some(parse_options);
+        self
+    }
+
+    /// Builds the renderer, detecting anything that has not been set.
+    pub fn build(self) -> Renderer {
+        let should_colorize = match self.color_choice.unwrap_or(ColorChoice::Auto) {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => std::io::stdout().is_terminal(),
+        };
+
+        Renderer {
+            theme: self.theme.unwrap_or_else(get_default_theme),
+            should_colorize,
+            options: RenderOptions {
+                width: self.width.unwrap_or_else(terminal_width),
+                hyperlinks: self.hyperlinks.unwrap_or(true),
+                images: self.image_protocol.unwrap_or_else(ImageProtocol::detect),
+                base_dir: self.base_dir,
+                max_image_bytes: self.max_image_bytes.unwrap_or(DEFAULT_MAX_IMAGE_BYTES),
+                color_depth: self.color_depth.unwrap_or_else(ColorDepth::detect),
+                footnotes: self.footnotes.unwrap_or_default(),
+                unicode: self.unicode.unwrap_or_else(is_unicode_locale),
+                link_fallback: self.link_fallback.unwrap_or_default(),
+                unknown_html: self.unknown_html.unwrap_or_default(),
+                frontmatter: self.frontmatter.unwrap_or_default(),
+            },
+            parse_options: self
+                .parse_options
+                .unwrap_or_else(writer::default_parse_options),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::themes::get_dark_theme;
+
+    fn renderer() -> RendererBuilder {
+        Renderer::builder()
+            .theme(get_dark_theme())
+            .color_choice(ColorChoice::Never)
+            .width(None)
+    }
+
+    #[test]
+    fn should_render_reader() {
+        let renderer = renderer().build();
+        let mut result = Vec::new();
+        renderer
+            .render_reader("**Hello**".as_bytes(), &mut result)
+            .unwrap();
+
+        assert_eq!(result, b"Hello");
+    }
+
+    #[test]
+    fn should_return_error_if_reader_is_not_utf8() {
+        let renderer = renderer().build();
+        let mut result = Vec::new();
+        let error = renderer.render_reader(&[0xFF, 0xFE][..], &mut result);
+
+        assert!(matches!(error, Err(Error::InvalidUtf8(_))));
+    }
+
+    #[test]
+    fn should_return_error_if_path_does_not_exist() {
+        let renderer = renderer().build();
+        let mut result = Vec::new();
+        let error = renderer.render_path("does-not-exist.md", &mut result);
+
+        assert!(matches!(error, Err(Error::Io(_))));
+    }
+
+    #[test]
+    fn should_wrap_to_width() {
+        let renderer = renderer().width(Some(10)).build();
+        let result = renderer.render_to_string("Hello big world").unwrap();
+
+        assert_eq!(result, "Hello big\nworld");
+    }
+
+    #[test]
+    fn should_not_write_hyperlinks_if_disabled() {
+        let renderer = renderer()
+            .color_choice(ColorChoice::Always)
+            .hyperlinks(false)
+            .build();
+        let result = renderer.render_to_string("<http://a.com>").unwrap();
+
+        assert!(!result.contains("\u{1b}]8;;"));
+        assert!(result.contains("http://a.com"));
+    }
+
+    #[test]
+    fn should_reduce_colors_to_color_depth() {
+        let renderer = renderer()
+            .color_choice(ColorChoice::Always)
+            .color_depth(ColorDepth::Ansi16)
+            .build();
+        let result = renderer.render_to_string("Some `code`").unwrap();
+
+        assert_eq!(result, "Some \u{1b}[40;91m code \u{1b}[0m");
+    }
+
+    #[test]
+    fn should_use_parse_options() {
+        let renderer = renderer().parse_options(ParseOptions::default()).build();
+        let result = renderer.render_to_string("~Delete~").unwrap();
+
+        assert_eq!(result, "~Delete~");
+    }
+}
@@ -0,0 +1,300 @@
+use super::Renderer;
+use crate::{writer, Error};
+use markdown::{mdast, ParseOptions};
+
+/// Renders markdown that arrives a chunk at a time, such as a response that is still
+/// being generated.
+///
+/// Blocks are written as soon as they are complete, which is when the next block has
+/// started. The last block is held back until more markdown arrives or the stream is
+/// finished, as it may still change. e.g. A code fence that has not been closed yet.
+/// Blocks that have been written are never written again.
+///
+/// Links and footnotes that use a definition are only resolved if the definition is in
+/// the same block, or in one of the blocks written with it. Footnotes and numbered links
+/// are written at the end when the stream is finished.
+///
+/// ### Example
+/// ```rust
+/// use markterm::{ColorChoice, Renderer};
+///
+/// let renderer = Renderer::builder().color_choice(ColorChoice::Never).build();
+/// let mut stream = renderer.stream(Vec::new());
+///
+/// stream.push("First para").unwrap();
+/// stream.push("graph\n\n```rust\nlet x = 1;\n").unwrap();
+/// assert_eq!(stream.get_ref(), b"First paragraph");
+///
+/// let output = stream.finish().unwrap();
+/// assert_eq!(output, b"First paragraph\n\nlet x = 1;\n");
+/// ```
+pub struct StreamRenderer<'a, W: std::io::Write> {
+    renderer: &'a Renderer,
+    writer: W,
+
+    /// The markdown that has been pushed but not written yet.
+    pending: String,
+
+    /// The last block that was written.
+    previous: Option<mdast::Node>,
+
+    /// The headings and footnotes that have been written, so that they are numbered
+    /// across blocks.
+    document: writer::Document,
+
+    /// The options that the markdown after the first block is parsed with. Frontmatter is
+    /// only at the start of a document, so it is not parsed again in the markdown that
+    /// follows, where a rule could otherwise be read as the start of frontmatter.
+    continuation_options: Option<ParseOptions>,
+}
+
+impl<'a, W: std::io::Write> StreamRenderer<'a, W> {
+    pub(super) fn new(renderer: &'a Renderer, writer: W) -> Self {
+        Self {
+            renderer,
+            writer,
+            pending: String::new(),
+            previous: None,
+            document: writer::Document::default(),
+            continuation_options: None,
+        }
+    }
+
+    /// Adds the chunk of markdown to the stream and writes any blocks that are now complete.
+    pub fn push(&mut self, chunk: &str) -> Result<(), Error> {
+        self.pending.push_str(chunk);
+        self.write_pending(false)
+    }
+
+    /// Writes the markdown that is still pending, followed by the footnotes, and returns
+    /// the writer.
+    pub fn finish(mut self) -> Result<W, Error> {
+        self.write_pending(true)?;
+        writer::write_end_of_document(
+            self.previous.as_ref(),
+            &self.document,
+            &self.renderer.theme,
+            &self.renderer.options,
+            &mut self.writer,
+            self.renderer.should_colorize,
+        )?;
+
+        Ok(self.writer)
+    }
+
+    /// Gets a reference to the writer.
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    fn write_pending(&mut self, is_finished: bool) -> Result<(), Error> {
+        // A line that has not ended yet could still turn into anything, so it is not parsed.
+        let complete = if is_finished {
+            self.pending.len()
+        } else {
+            self.pending.rfind('\n').map_or(0, |index| index + 1)
+        };
+
+        if self.pending[..complete].trim().is_empty() {
+            return Ok(());
+        }
+
+        let parse_options = match &self.continuation_options {
+            Some(options) => options,
+            None => &self.renderer.parse_options,
+        };
+
+        let ast = markdown::to_mdast(&self.pending[..complete], parse_options)?;
+        let mdast::Node::Root(root) = ast else {
+            return Ok(());
+        };
+
+        let mut blocks = root.children;
+
+        // The last block can be a definition that the blocks written now use.
+        self.document.collect_definitions(&blocks);
+        let written = if is_finished {
+            self.pending.len()
+        } else {
+            // The last block is kept until the next one starts, so that it can be parsed
+            // again with the rest of its lines.
+            let Some(offset) = blocks
+                .pop()
+                .and_then(|last| last.position().map(|position| position.start.offset))
+            else {
+                return Ok(());
+            };
+
+            // Blocks are parsed again from the start of their line, so that their indentation
+            // is kept.
+            self.pending[..offset]
+                .rfind('\n')
+                .map_or(0, |index| index + 1)
+        };
+
+        if blocks.is_empty() {
+            return Ok(());
+        }
+
+        writer::write_top_level_blocks(
+            &blocks,
+            self.previous.as_ref(),
+            &self.document,
+            &self.renderer.theme,
+            &self.renderer.options,
+            &mut self.writer,
+            self.renderer.should_colorize,
+        )?;
+        self.writer.flush()?;
+
+        if let Some(last) = blocks
+            .into_iter()
+            .rev()
+            .find(|block| writer::is_written_in_place(block, self.renderer.options.footnotes))
+        {
+            self.previous = Some(last);
+        }
+
+        self.pending.drain(..written);
+        if self.continuation_options.is_none() {
+            self.continuation_options = Some(continuation_options(&self.renderer.parse_options));
+        }
+
+        Ok(())
+    }
+}
+
+/// Copies the options without frontmatter. The functions that parse MDX expressions and
+/// ESM cannot be copied, so they are left out.
+fn continuation_options(options: &ParseOptions) -> ParseOptions {
+    let mut constructs = options.constructs.clone();
+    constructs.frontmatter = false;
+
+    ParseOptions {
+        constructs,
+        gfm_strikethrough_single_tilde: options.gfm_strikethrough_single_tilde,
+        math_text_single_dollar: options.math_text_single_dollar,
+        ..ParseOptions::default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::themes::get_dark_theme;
+    use crate::{ColorChoice, HeadingFormat, HeadingPrefix, Renderer, Theme};
+
+    fn renderer() -> Renderer {
+        Renderer::builder()
+            .theme(get_dark_theme())
+            .color_choice(ColorChoice::Never)
+            .width(None)
+            .build()
+    }
+
+    #[test]
+    fn should_hold_back_the_last_block() {
+        let renderer = renderer();
+        let mut stream = renderer.stream(Vec::new());
+
+        stream.push("# Title\n\nSome text\n").unwrap();
+        assert_eq!(stream.get_ref(), b"\n  Title  \n\n");
+
+        stream.push("\nMore text\n").unwrap();
+        assert_eq!(stream.get_ref(), b"\n  Title  \n\nSome text");
+
+        let output = stream.finish().unwrap();
+        assert_eq!(output, b"\n  Title  \n\nSome text\n\nMore text");
+    }
+
+    #[test]
+    fn should_hold_back_unterminated_code_fences() {
+        let renderer = renderer();
+        let mut stream = renderer.stream(Vec::new());
+
+        stream.push("Text\n\n```\nfirst\n\n").unwrap();
+        stream.push("second\n").unwrap();
+        assert_eq!(stream.get_ref(), b"Text");
+
+        stream.push("```\n\nDone\n").unwrap();
+        assert_eq!(stream.get_ref(), b"Text\n\nfirst\n\nsecond\n");
+    }
+
+    #[test]
+    fn should_write_footnotes_when_finished() {
+        let renderer = renderer();
+        let mut stream = renderer.stream(Vec::new());
+
+        stream.push("A[^a]\n\n[^a]: Note\n\nEnd\n").unwrap();
+        assert_eq!(stream.get_ref(), b"A[1]");
+
+        let output = stream.finish().unwrap();
+        assert_eq!(output, b"A[1]\n\nEnd\n\nFootnotes\n[1] Note\n");
+    }
+
+    #[test]
+    fn should_not_parse_frontmatter_after_the_first_block() {
+        let renderer = renderer();
+        let mut stream = renderer.stream(Vec::new());
+
+        stream.push("Intro\n\n---\n").unwrap();
+        stream.push("title: x\n---\n").unwrap();
+        stream.push("\nEnd\n").unwrap();
+
+        let output = String::from_utf8(stream.finish().unwrap()).unwrap();
+        let expected = renderer
+            .render_to_string("Intro\n\n---\ntitle: x\n---\n\nEnd\n")
+            .unwrap();
+        assert!(output.contains("title: x"));
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn should_number_headings_across_blocks() {
+        let theme = Theme {
+            header_formats: vec![HeadingFormat::new(HeadingPrefix::Numbers, None)],
+            ..get_dark_theme()
+        };
+        let renderer = Renderer::builder()
+            .theme(theme)
+            .color_choice(ColorChoice::Never)
+            .build();
+        let mut stream = renderer.stream(Vec::new());
+
+        stream
+            .push(
+                "# One
+
+## A
+
+",
+            )
+            .unwrap();
+        stream
+            .push(
+                "## B
+
+# Two
+",
+            )
+            .unwrap();
+
+        let output = String::from_utf8(stream.finish().unwrap()).unwrap();
+        assert!(output.contains(" 1.2 B "));
+        assert!(output.contains(" 2 Two "));
+    }
+
+    #[test]
+    fn should_match_rendering_the_whole_document() {
+        let input = "# Title\n\nA paragraph\nwith two lines\n\n- one\n- two\n\n> quote\n\n1. a\n2. b\n\nEnd";
+        let renderer = renderer();
+        let expected = renderer.render_to_string(input).unwrap();
+
+        let mut stream = renderer.stream(Vec::new());
+        for chunk in input.as_bytes().chunks(3) {
+            stream.push(std::str::from_utf8(chunk).unwrap()).unwrap();
+        }
+
+        let output = stream.finish().unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+    }
+}
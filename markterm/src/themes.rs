@@ -1,78 +1,358 @@
 /// A module to assist with setting colors.
 pub mod color;
 
-use color::Color;
+/// Loads themes from TOML, JSON and YAML files.
+#[cfg(feature = "serde")]
+mod file;
 
-/// Indicates whether the text is bold, underlined, italics or strikethrough
-#[derive(PartialEq, Default)]
-pub enum TextStyle {
-    /// Indicates normal text.
+/// The attributes of text such as bold and underlined.
+mod style;
+
+use color::{Color, ColorDepth};
+
+#[cfg(feature = "serde")]
+pub use file::ThemeFormat;
+pub use style::TextStyle;
+
+/// The numbering system used for the items of ordered lists.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum NumberStyle {
+    /// Numbers items as 1, 2, 3.
     #[default]
-    Normal,
+    Decimal,
 
-    /// Indicates **bold text**.
-    Bold,
+    /// Numbers items as a, b, c.
+    LowerAlpha,
 
-    /// Indicates *italicised text*.
-    Italics,
+    /// Numbers items as A, B, C.
+    UpperAlpha,
 
-    /// Indicates <ins>underlined text</ins>.
-    Underlined,
+    /// Numbers items as i, ii, iii.
+    LowerRoman,
 
-    /// Indicates ~~Strikethrough text~~.
-    Strikethrough,
+    /// Numbers items as I, II, III.
+    UpperRoman,
 }
 
-impl TextStyle {
-    /// Gets the console style key for the text style without escape characters.
-    pub fn style_key(&self) -> &str {
+impl NumberStyle {
+    /// Converts the number into the numbering system.
+    ///
+    /// Letters and roman numerals cannot represent 0, so it is written as a decimal.
+    /// ### Example
+    /// ```rust
+    /// use markterm::NumberStyle;
+    ///
+    /// assert_eq!(NumberStyle::UpperRoman.format(14), "XIV");
+    /// assert_eq!(NumberStyle::LowerAlpha.format(28), "ab");
+    /// ```
+    pub fn format(&self, number: u32) -> String {
+        if number == 0 {
+            return number.to_string();
+        }
+
         match self {
-            TextStyle::Normal => "",
-            TextStyle::Bold => "1",
-            TextStyle::Italics => "3",
-            TextStyle::Underlined => "4",
-            TextStyle::Strikethrough => "9",
+            NumberStyle::Decimal => number.to_string(),
+            NumberStyle::LowerAlpha => to_alpha(number),
+            NumberStyle::UpperAlpha => to_alpha(number).to_uppercase(),
+            NumberStyle::LowerRoman => to_roman(number),
+            NumberStyle::UpperRoman => to_roman(number).to_uppercase(),
+        }
+    }
+}
+
+fn to_alpha(mut number: u32) -> String {
+    let mut letters = Vec::new();
+    while number > 0 {
+        number -= 1;
+        letters.push((b'a' + (number % 26) as u8) as char);
+        number /= 26;
+    }
+
+    letters.iter().rev().collect()
+}
+
+fn to_roman(mut number: u32) -> String {
+    const NUMERALS: [(u32, &str); 13] = [
+        (1000, "m"),
+        (900, "cm"),
+        (500, "d"),
+        (400, "cd"),
+        (100, "c"),
+        (90, "xc"),
+        (50, "l"),
+        (40, "xl"),
+        (10, "x"),
+        (9, "ix"),
+        (5, "v"),
+        (4, "iv"),
+        (1, "i"),
+    ];
+
+    let mut roman = String::new();
+    for (value, numeral) in NUMERALS {
+        while number >= value {
+            roman.push_str(numeral);
+            number -= value;
         }
     }
+
+    roman
+}
+
+/// The format of the numbers written in front of the items of ordered lists.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(deny_unknown_fields))]
+pub struct NumberFormat {
+    /// The numbering system.
+    pub style: NumberStyle,
+
+    /// The text written after the number. e.g. `.` for `1.` or `)` for `1)`.
+    pub suffix: String,
+}
+
+impl NumberFormat {
+    /// Creates a new instance of NumberFormat.
+    ///
+    /// Example
+    /// ```rust
+    /// use markterm::{NumberFormat, NumberStyle};
+    /// let format = NumberFormat::new(NumberStyle::LowerRoman, ")");
+    /// assert_eq!(format.format(3), "iii)");
+    /// ```
+    pub fn new(style: NumberStyle, suffix: &str) -> Self {
+        Self {
+            style,
+            suffix: suffix.to_string(),
+        }
+    }
+
+    /// Formats the number of a list item.
+    pub fn format(&self, number: u32) -> String {
+        format!("{}{}", self.style.format(number), self.suffix)
+    }
+}
+
+/// The text written in front of a heading.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum HeadingPrefix {
+    /// Nothing is written in front of the heading.
+    #[default]
+    None,
+
+    /// A `#` is written for every level of the heading. e.g. `###` for level 3.
+    Hashes,
+
+    /// The heading is numbered within the headings that enclose it. e.g. `1.2.3`.
+    ///
+    /// The numbers start from the first level that is numbered, so headings of level 1
+    /// can be left as the title of the document while the rest are numbered.
+    Numbers,
+}
+
+/// How a heading is decorated.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+pub struct HeadingFormat {
+    /// The text written in front of the heading.
+    pub prefix: HeadingPrefix,
+
+    /// The character repeated under the heading to draw a rule under it.
+    /// No rule is drawn when this is `None`.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub underline: Option<char>,
+}
+
+impl HeadingFormat {
+    /// Creates a new instance of HeadingFormat.
+    ///
+    /// Example
+    /// ```rust
+    /// use markterm::{HeadingFormat, HeadingPrefix};
+    /// let format = HeadingFormat::new(HeadingPrefix::Numbers, Some('═'));
+    /// assert_eq!(format.underline, Some('═'));
+    /// ```
+    pub fn new(prefix: HeadingPrefix, underline: Option<char>) -> Self {
+        Self { prefix, underline }
+    }
 }
 
 /// Properties required to theme the element.
+///
+/// When it is read from a theme file, the properties that are left out are not set.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(deny_unknown_fields))]
 pub struct ElementTheme {
     /// Foreground color. i.e text color
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
     pub fg: Option<Color>,
 
     /// Background color
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
     pub bg: Option<Color>,
 
     /// Indicates the text style.
+    #[cfg_attr(feature = "serde", serde(default))]
     pub style: TextStyle,
 }
 
+/// The themes for the tokens of code blocks that are highlighted.
+///
+/// Highlighting requires the `syntax-highlighting` feature. Text that does not
+/// belong to any of these tokens uses the theme of the code block.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(deny_unknown_fields))]
+pub struct SyntaxTheme {
+    /// The theme for comments.
+    pub comment: ElementTheme,
+
+    /// The theme for keywords such as `fn`, `if` and `return`.
+    pub keyword: ElementTheme,
+
+    /// The theme for string and character literals.
+    pub string: ElementTheme,
+
+    /// The theme for numbers and other constants such as `true`.
+    pub number: ElementTheme,
+
+    /// The theme for the names of functions.
+    pub function: ElementTheme,
+
+    /// The theme for the names of types.
+    pub type_name: ElementTheme,
+}
+
 /// A top level struct that contains all the elements and their styles.
+///
+/// With the `serde` feature, themes can also be read from files that only set some of
+/// the elements. See `Theme::from_str`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(deny_unknown_fields))]
 pub struct Theme {
     /// The theme for header 1 elements.
     ///
     /// Header elements start with #)
     pub header_1: ElementTheme,
 
-    /// The theme for all header 2, 3 and 4
-    pub header_x: ElementTheme,
+    /// The theme for header 2 elements. i.e `## Header`
+    pub header_2: ElementTheme,
+
+    /// The theme for header 3 elements.
+    pub header_3: ElementTheme,
+
+    /// The theme for header 4 elements.
+    pub header_4: ElementTheme,
+
+    /// The theme for header 5 elements.
+    pub header_5: ElementTheme,
+
+    /// The theme for header 6 elements.
+    pub header_6: ElementTheme,
 
-    /// The theme for code blocks.
-    /// Code blocks are elements that are surrounded by ``
+    /// The prefixes and rules of the headers of each level, starting from level 1.
+    /// Levels after the last format use the last format.
+    pub header_formats: Vec<HeadingFormat>,
+
+    /// The theme for inline code. i.e `` `code` ``
+    pub code_inline: ElementTheme,
+
+    /// The theme for fenced and indented code blocks. Its background fills the panel
+    /// that the code block is written in.
     pub code_block: ElementTheme,
 
+    /// The theme for the language written above a code block.
+    pub code_block_label: ElementTheme,
+
+    /// The theme for the numbers of the lines of a code block.
+    pub code_block_gutter: ElementTheme,
+
+    /// Indicates whether the language of a code block is written above it.
+    pub code_block_show_label: bool,
+
+    /// Indicates whether the lines of a code block are numbered.
+    pub code_block_show_line_numbers: bool,
+
+    /// The number of columns left empty on both sides of a code block.
+    pub code_block_margin: usize,
+
+    /// The themes for the tokens of fenced code blocks whose language is known.
+    pub syntax: SyntaxTheme,
+
     /// The theme for indentations
     /// Indent Elements start with >
     pub indents: ElementTheme,
 
-    /// The theme for links.
-    /// Links are surrounded by < >
+    /// The bars drawn in front of the lines of block quotes. Nested block quotes use the
+    /// next bar in the list and wrap around to the first one when they run out.
+    pub indent_bars: Vec<char>,
+
+    /// The themes of the bars of each level of nested block quotes, which are combined
+    /// with `indents`. Deeper block quotes wrap around to the first theme.
+    pub indent_levels: Vec<ElementTheme>,
+
+    /// The theme for the title and the bar of `[!NOTE]` alerts.
+    pub alert_note: ElementTheme,
+
+    /// The theme for the title and the bar of `[!TIP]` alerts.
+    pub alert_tip: ElementTheme,
+
+    /// The theme for the title and the bar of `[!IMPORTANT]` alerts.
+    pub alert_important: ElementTheme,
+
+    /// The theme for the title and the bar of `[!WARNING]` alerts.
+    pub alert_warning: ElementTheme,
+
+    /// The theme for the title and the bar of `[!CAUTION]` alerts.
+    pub alert_caution: ElementTheme,
+
+    /// The theme for the text of links.
     pub link: ElementTheme,
 
-    /// The theme for lists
+    /// The theme for the markers of footnotes and the title of the footnotes section.
+    pub footnote: ElementTheme,
+
+    /// Indicates whether the markers of footnotes are superscript numbers such as `¹`
+    /// rather than `[1]`.
+    pub footnote_superscripts: bool,
+
+    /// The theme for the placeholders written for images that cannot be drawn.
+    pub image: ElementTheme,
+
+    /// The theme for the bullets of list items.
     pub list: ElementTheme,
 
+    /// The bullets used for list items. Nested lists use the next bullet
+    /// in the list and wrap around to the first one when they run out.
+    pub list_bullets: Vec<char>,
+
+    /// The formats used for the numbers of ordered lists. Nested lists use the
+    /// next format in the list and wrap around to the first one when they run out.
+    pub list_number_formats: Vec<NumberFormat>,
+
+    /// The number of columns the text of a list item is indented from its bullet.
+    /// Nested lists and continuation lines are aligned to this indent.
+    pub list_indent: usize,
+
+    /// The theme for task list items that have been checked. i.e `- [x]`
+    pub task_checked: ElementTheme,
+
+    /// The theme for task list items that have not been checked. i.e `- [ ]`
+    pub task_unchecked: ElementTheme,
+
     /// The theme for bold text.
     pub strong: ElementTheme,
 
@@ -81,11 +361,40 @@ pub struct Theme {
 
     /// The theme for strikethroughs
     pub delete: ElementTheme,
+
+    /// The theme for the cells in the header row of a table.
+    pub table_header: ElementTheme,
+
+    /// The theme for the borders drawn around table cells.
+    pub table_border: ElementTheme,
+
+    /// The theme for keys written with the HTML `<kbd>` tag.
+    pub html_kbd: ElementTheme,
+
+    /// The theme for the summary of an HTML `<details>` element.
+    pub html_summary: ElementTheme,
+
+    /// The theme for HTML tags that are not known, when they are written dimmed.
+    pub html_tag: ElementTheme,
+
+    /// The theme for the table of keys and values written for the frontmatter of a document.
+    pub frontmatter: ElementTheme,
+
+    /// The theme for the banner written with the title in the frontmatter of a document.
+    pub frontmatter_banner: ElementTheme,
+
+    /// The theme for inline math and math blocks. i.e `$x^2$`
+    pub math: ElementTheme,
+
+    /// The theme for the rules written for thematic breaks. i.e `---`
+    pub rule: ElementTheme,
+
+    /// The character that rules are drawn with. `-` is used instead when the output is
+    /// not Unicode and the character is not ASCII.
+    pub rule_char: char,
 }
 
 const T_ESC: &str = "\u{1b}";
-const T_FG: &str = "38";
-const T_BG: &str = "48";
 
 impl ElementTheme {
     /// Creates a new instance of ElementTheme.
@@ -95,6 +404,9 @@ impl ElementTheme {
     /// use markterm::{ElementTheme,TextStyle};
     /// let a = ElementTheme::new(Some("#000"), Some("#FFF"), TextStyle::Normal);
     /// ```
+    ///
+    /// ### Panics
+    /// Panics if either of the colors is not a valid hex code.
     pub fn new(fg: Option<&str>, bg: Option<&str>, style: TextStyle) -> Self {
         let bg_color = bg.map(Color::new);
         let fg_color = fg.map(Color::new);
@@ -126,63 +438,192 @@ impl ElementTheme {
         F: Fn(&mut T) -> Result<(), std::io::Error>,
         T: std::io::Write,
     {
-        if !is_writer_tty {
-            return write_text(writer);
-        }
+        let depth = is_writer_tty.then_some(ColorDepth::TrueColor);
+        self.write_with_depth(write_text, writer, depth)
+    }
 
-        let style_key = match self.style {
-            TextStyle::Normal => "".to_string(),
-            _ => format!("{};", self.style.style_key()),
-        };
-        match (&self.fg, &self.bg) {
-            (Some(fg), Some(bg)) => {
-                write!(
-                    writer,
-                    "{T_ESC}[{style_key}{T_BG};2;{};{T_FG};2;{}m",
-                    bg.rgb(),
-                    fg.rgb()
-                )?;
-            }
-            (Some(fg), None) => {
-                write!(writer, "{T_ESC}[{style_key}{T_FG};2;{}m", fg.rgb())?;
-            }
-            (None, Some(bg)) => {
-                write!(writer, "{T_ESC}[{style_key}{T_BG};2;{}m", bg.rgb())?;
-            }
-            (None, None) => {
-                if self.style != TextStyle::Normal {
-                    write!(writer, "{T_ESC}[{}m", self.style.style_key())?;
-                }
-            }
+    /// Writes the current theme into the terminal, with the colors reduced to the
+    /// colors that the terminal can display. When `depth` is `None`, the text is written
+    /// without escape sequences.
+    /// ### Usage
+    /// ```rust
+    /// use std::io::Write;
+    /// use markterm::{ColorDepth, TextStyle, ElementTheme};
+    ///
+    /// let element_theme = ElementTheme::new(Some("#F00"), None, TextStyle::Normal);
+    ///
+    /// let mut result = Vec::new();
+    /// element_theme
+    ///     .write_with_depth(|w| write!(w, "Hello"), &mut result, Some(ColorDepth::Ansi256))
+    ///     .unwrap();
+    /// assert_eq!(result, b"\x1b[38;5;196mHello\x1b[0m");
+    /// ```
+    pub fn write_with_depth<F, T>(
+        &self,
+        write_text: F,
+        writer: &mut T,
+        depth: Option<ColorDepth>,
+    ) -> Result<(), std::io::Error>
+    where
+        F: Fn(&mut T) -> Result<(), std::io::Error>,
+        T: std::io::Write,
+    {
+        let Some(depth) = depth else {
+            return write_text(writer);
         };
 
+        self.write_start(writer, depth)?;
         write_text(writer)?;
 
-        match self {
-            ElementTheme {
-                fg: None,
-                bg: None,
-                style: TextStyle::Normal,
-            } => {
-                write!(writer, "")
-            }
-            _ => write!(writer, "{T_ESC}[0m"),
+        if self.is_plain() {
+            Ok(())
+        } else {
+            write!(writer, "{T_ESC}[0m")
         }
     }
+
+    /// Writes the text inside an element that has already been themed with `enclosing`.
+    ///
+    /// The text is written with the colors and styles of both themes. Once it has been
+    /// written, the enclosing theme is restored rather than reset, so that the rest of
+    /// the enclosing element is still themed.
+    ///
+    /// ### Usage
+    /// ```rust
+    /// use std::io::Write;
+    /// use markterm::{ColorDepth, TextStyle, ElementTheme};
+    ///
+    /// let strong = ElementTheme::new(None, None, TextStyle::Bold);
+    /// let emphasis = ElementTheme::new(None, None, TextStyle::Italics);
+    ///
+    /// let mut result = Vec::new();
+    /// emphasis
+    ///     .write_nested(&strong, |w| write!(w, "Hello"), &mut result, Some(ColorDepth::TrueColor))
+    ///     .unwrap();
+    /// assert_eq!(result, b"\x1b[1;3mHello\x1b[0m\x1b[1m");
+    /// ```
+    pub fn write_nested<F, T>(
+        &self,
+        enclosing: &ElementTheme,
+        write_text: F,
+        writer: &mut T,
+        depth: Option<ColorDepth>,
+    ) -> Result<(), std::io::Error>
+    where
+        F: Fn(&mut T) -> Result<(), std::io::Error>,
+        T: std::io::Write,
+    {
+        enclosing
+            .combine(self)
+            .write_with_depth(write_text, writer, depth)?;
+
+        if let Some(depth) = depth {
+            enclosing.write_start(writer, depth)?;
+        }
+
+        Ok(())
+    }
+
+    /// Combines the theme of an element with the theme of an element inside it.
+    ///
+    /// The colors of the inner element are used when they are set, and the text styles
+    /// of both elements are applied.
+    pub fn combine(&self, inner: &ElementTheme) -> ElementTheme {
+        ElementTheme {
+            fg: inner.fg.or(self.fg),
+            bg: inner.bg.or(self.bg),
+            style: self.style | inner.style,
+        }
+    }
+
+    /// Indicates whether the theme leaves text as it is.
+    pub fn is_plain(&self) -> bool {
+        self.fg.is_none() && self.bg.is_none() && self.style.is_normal()
+    }
+
+    /// Writes the escape sequence that starts the theme. Nothing is written for plain themes.
+    pub(crate) fn write_start(
+        &self,
+        writer: &mut impl std::io::Write,
+        depth: ColorDepth,
+    ) -> std::io::Result<()> {
+        let params: Vec<String> = [
+            (!self.style.is_normal()).then(|| self.style.style_key()),
+            self.bg.map(|bg| bg.sgr(true, depth)),
+            self.fg.map(|fg| fg.sgr(false, depth)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        if params.is_empty() {
+            return Ok(());
+        }
+
+        write!(writer, "{T_ESC}[{}m", params.join(";"))
+    }
 }
 
 ///Gets the default dark theme
 pub fn get_dark_theme() -> Theme {
     Theme {
         header_1: ElementTheme::new(None, Some("#6155FB"), TextStyle::Normal),
-        header_x: ElementTheme::new(Some("#01AFFD"), None, TextStyle::Normal),
-        code_block: ElementTheme::new(Some("#FF6060"), Some("#303030"), TextStyle::Normal),
+        header_2: ElementTheme::new(Some("#01AFFD"), None, TextStyle::Normal),
+        header_3: ElementTheme::new(Some("#01AFFD"), None, TextStyle::Normal),
+        header_4: ElementTheme::new(Some("#01AFFD"), None, TextStyle::Normal),
+        header_5: ElementTheme::new(Some("#01AFFD"), None, TextStyle::Italics),
+        header_6: ElementTheme::new(Some("#7F848E"), None, TextStyle::Italics),
+        header_formats: default_header_formats(),
+        code_inline: ElementTheme::new(Some("#FF6060"), Some("#303030"), TextStyle::Normal),
+        code_block: ElementTheme::new(Some("#ABB2BF"), Some("#303030"), TextStyle::Normal),
+        code_block_label: ElementTheme::new(Some("#7F848E"), None, TextStyle::Italics),
+        code_block_gutter: ElementTheme::new(Some("#5C6370"), None, TextStyle::Normal),
+        code_block_show_label: true,
+        code_block_show_line_numbers: false,
+        code_block_margin: 1,
+        syntax: SyntaxTheme {
+            comment: ElementTheme::new(Some("#7F848E"), None, TextStyle::Italics),
+            keyword: ElementTheme::new(Some("#C678DD"), None, TextStyle::Normal),
+            string: ElementTheme::new(Some("#98C379"), None, TextStyle::Normal),
+            number: ElementTheme::new(Some("#D19A66"), None, TextStyle::Normal),
+            function: ElementTheme::new(Some("#61AFEF"), None, TextStyle::Normal),
+            type_name: ElementTheme::new(Some("#E5C07B"), None, TextStyle::Normal),
+        },
         indents: ElementTheme::new(Some("#555"), None, TextStyle::Normal),
+        indent_bars: default_indent_bars(),
+        indent_levels: vec![
+            ElementTheme::default(),
+            ElementTheme::new(Some("#6155FB"), None, TextStyle::Normal),
+            ElementTheme::new(Some("#008787"), None, TextStyle::Normal),
+        ],
+        alert_note: ElementTheme::new(Some("#4493F8"), None, TextStyle::Bold),
+        alert_tip: ElementTheme::new(Some("#3FB950"), None, TextStyle::Bold),
+        alert_important: ElementTheme::new(Some("#AB7DF8"), None, TextStyle::Bold),
+        alert_warning: ElementTheme::new(Some("#D29922"), None, TextStyle::Bold),
+        alert_caution: ElementTheme::new(Some("#F85149"), None, TextStyle::Bold),
         link: ElementTheme::new(Some("#008787"), None, TextStyle::Underlined),
+        image: ElementTheme::new(Some("#008787"), None, TextStyle::Italics),
+        footnote: ElementTheme::new(Some("#008787"), None, TextStyle::Normal),
+        footnote_superscripts: false,
         list: ElementTheme::new(None, None, TextStyle::Normal),
+        list_bullets: vec!['•', '◦', '▪', '▫'],
+        list_number_formats: default_number_formats(),
+        list_indent: 2,
+        task_checked: ElementTheme::new(Some("#777"), None, TextStyle::Strikethrough),
+        task_unchecked: ElementTheme::new(None, None, TextStyle::Normal),
         strong: ElementTheme::new(None, None, TextStyle::Bold),
         emphasis: ElementTheme::new(None, None, TextStyle::Italics),
         delete: ElementTheme::new(None, None, TextStyle::Strikethrough),
+        table_header: ElementTheme::new(Some("#01AFFD"), None, TextStyle::Bold),
+        table_border: ElementTheme::new(Some("#555"), None, TextStyle::Normal),
+        html_kbd: ElementTheme::new(Some("#E4E4E4"), Some("#4E4E4E"), TextStyle::Bold),
+        html_summary: ElementTheme::new(None, None, TextStyle::Bold),
+        html_tag: ElementTheme::new(None, None, TextStyle::Dim),
+        frontmatter: ElementTheme::new(None, None, TextStyle::Dim),
+        frontmatter_banner: ElementTheme::new(Some("#FFF"), Some("#6155FB"), TextStyle::Bold),
+        math: ElementTheme::new(Some("#E5C07B"), None, TextStyle::Normal),
+        rule: ElementTheme::new(Some("#555"), None, TextStyle::Normal),
+        rule_char: '─',
     }
 }
 
@@ -190,17 +631,85 @@ pub fn get_dark_theme() -> Theme {
 pub fn get_light_theme() -> Theme {
     Theme {
         header_1: ElementTheme::new(Some("#FFF"), Some("#6155FB"), TextStyle::Normal),
-        header_x: ElementTheme::new(Some("#01AFFD"), None, TextStyle::Normal),
-        code_block: ElementTheme::new(Some("#EA3323"), Some("#E4E4E4"), TextStyle::Normal),
+        header_2: ElementTheme::new(Some("#01AFFD"), None, TextStyle::Normal),
+        header_3: ElementTheme::new(Some("#01AFFD"), None, TextStyle::Normal),
+        header_4: ElementTheme::new(Some("#01AFFD"), None, TextStyle::Normal),
+        header_5: ElementTheme::new(Some("#01AFFD"), None, TextStyle::Italics),
+        header_6: ElementTheme::new(Some("#A0A1A7"), None, TextStyle::Italics),
+        header_formats: default_header_formats(),
+        code_inline: ElementTheme::new(Some("#EA3323"), Some("#E4E4E4"), TextStyle::Normal),
+        code_block: ElementTheme::new(Some("#383A42"), Some("#F0F0F0"), TextStyle::Normal),
+        code_block_label: ElementTheme::new(Some("#A0A1A7"), None, TextStyle::Italics),
+        code_block_gutter: ElementTheme::new(Some("#A0A1A7"), None, TextStyle::Normal),
+        code_block_show_label: true,
+        code_block_show_line_numbers: false,
+        code_block_margin: 1,
+        syntax: SyntaxTheme {
+            comment: ElementTheme::new(Some("#A0A1A7"), None, TextStyle::Italics),
+            keyword: ElementTheme::new(Some("#A626A4"), None, TextStyle::Normal),
+            string: ElementTheme::new(Some("#50A14F"), None, TextStyle::Normal),
+            number: ElementTheme::new(Some("#986801"), None, TextStyle::Normal),
+            function: ElementTheme::new(Some("#4078F2"), None, TextStyle::Normal),
+            type_name: ElementTheme::new(Some("#C18401"), None, TextStyle::Normal),
+        },
         indents: ElementTheme::new(None, None, TextStyle::Normal),
+        indent_bars: default_indent_bars(),
+        indent_levels: vec![
+            ElementTheme::default(),
+            ElementTheme::new(Some("#6155FB"), None, TextStyle::Normal),
+            ElementTheme::new(Some("#5CBC9A"), None, TextStyle::Normal),
+        ],
+        alert_note: ElementTheme::new(Some("#0969DA"), None, TextStyle::Bold),
+        alert_tip: ElementTheme::new(Some("#1A7F37"), None, TextStyle::Bold),
+        alert_important: ElementTheme::new(Some("#8250DF"), None, TextStyle::Bold),
+        alert_warning: ElementTheme::new(Some("#9A6700"), None, TextStyle::Bold),
+        alert_caution: ElementTheme::new(Some("#CF222E"), None, TextStyle::Bold),
         link: ElementTheme::new(Some("#5CBC9A"), None, TextStyle::Underlined),
+        image: ElementTheme::new(Some("#5CBC9A"), None, TextStyle::Italics),
+        footnote: ElementTheme::new(Some("#5CBC9A"), None, TextStyle::Normal),
+        footnote_superscripts: false,
         list: ElementTheme::new(None, None, TextStyle::Normal),
+        list_bullets: vec!['•', '◦', '▪', '▫'],
+        list_number_formats: default_number_formats(),
+        list_indent: 2,
+        task_checked: ElementTheme::new(Some("#999"), None, TextStyle::Strikethrough),
+        task_unchecked: ElementTheme::new(None, None, TextStyle::Normal),
         strong: ElementTheme::new(None, None, TextStyle::Bold),
         emphasis: ElementTheme::new(None, None, TextStyle::Italics),
         delete: ElementTheme::new(None, None, TextStyle::Strikethrough),
+        table_header: ElementTheme::new(Some("#6155FB"), None, TextStyle::Bold),
+        table_border: ElementTheme::new(Some("#999"), None, TextStyle::Normal),
+        html_kbd: ElementTheme::new(Some("#383A42"), Some("#DADADA"), TextStyle::Bold),
+        html_summary: ElementTheme::new(None, None, TextStyle::Bold),
+        html_tag: ElementTheme::new(None, None, TextStyle::Dim),
+        frontmatter: ElementTheme::new(None, None, TextStyle::Dim),
+        frontmatter_banner: ElementTheme::new(Some("#FFF"), Some("#6155FB"), TextStyle::Bold),
+        math: ElementTheme::new(Some("#986801"), None, TextStyle::Normal),
+        rule: ElementTheme::new(Some("#999"), None, TextStyle::Normal),
+        rule_char: '─',
     }
 }
 
+/// Headers of level 1 are written on their own, and the rest start with their `#`s.
+fn default_header_formats() -> Vec<HeadingFormat> {
+    vec![
+        HeadingFormat::new(HeadingPrefix::None, None),
+        HeadingFormat::new(HeadingPrefix::Hashes, None),
+    ]
+}
+
+fn default_indent_bars() -> Vec<char> {
+    vec!['│', '┆', '╎']
+}
+
+fn default_number_formats() -> Vec<NumberFormat> {
+    vec![
+        NumberFormat::new(NumberStyle::Decimal, "."),
+        NumberFormat::new(NumberStyle::LowerAlpha, "."),
+        NumberFormat::new(NumberStyle::LowerRoman, "."),
+    ]
+}
+
 /// Gets the default theme. The default theme is based on whether the terminal
 /// has a dark background or a light background.
 pub fn get_default_theme() -> Theme {
@@ -225,6 +734,32 @@ fn get_terminal_theme() -> Option<termbg::Theme> {
 
 #[cfg(test)]
 mod test {
+    mod number_style {
+        use super::super::*;
+
+        macro_rules! format_test {
+            ($($name:ident: $value:expr,)*) => {
+                $(
+                    #[test]
+                    fn $name() {
+                        let (style, number, expected) = $value;
+                        assert_eq!(style.format(number), expected);
+                    }
+                )*
+            }
+        }
+
+        format_test! {
+            should_format_decimal: (NumberStyle::Decimal, 12, "12"),
+            should_format_lower_alpha: (NumberStyle::LowerAlpha, 1, "a"),
+            should_format_upper_alpha: (NumberStyle::UpperAlpha, 26, "Z"),
+            should_wrap_alpha_after_z: (NumberStyle::LowerAlpha, 27, "aa"),
+            should_format_lower_roman: (NumberStyle::LowerRoman, 4, "iv"),
+            should_format_upper_roman: (NumberStyle::UpperRoman, 1994, "MCMXCIV"),
+            should_format_zero_as_decimal: (NumberStyle::UpperRoman, 0, "0"),
+        }
+    }
+
     mod write {
         use super::super::*;
         use crate::ElementTheme;
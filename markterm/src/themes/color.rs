@@ -1,25 +1,114 @@
-/// A simple struct to represent the color in the RGB format.
-pub struct Color {
-    /// A number between 0 and 255 which represents Red spectrum.
-    pub r: u8,
-    /// A number between 0 and 255 which represents Green spectrum.
-    pub g: u8,
-    /// A number between 0 and 255 which represents Blue spectrum.
-    pub b: u8,
+use crate::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// A color used by a theme.
+///
+/// Colors from the palette of the terminal follow the theme of the terminal, while RGB
+/// colors are the same everywhere.
+///
+/// Colors are read from text with [`Color::new`] or [`str::parse`], which accept:
+/// - The names of the 16 ANSI colors. e.g. `red` or `bright_blue`.
+/// - `ansi:N` for the ANSI color with the index N, from 0 to 15. e.g. `ansi:12`.
+/// - `256:N` for the color with the index N in the xterm 256 color palette. e.g. `256:208`.
+/// - Hex codes with 3 or 6 digits. e.g. `#F00` or `#FF0000`.
+/// - `rgb(r, g, b)` where every value is from 0 to 255.
+/// - `hsl(h, s%, l%)` where the hue is in degrees.
+///
+/// ### Example
+/// ```rust
+/// use markterm::Color;
+///
+/// assert_eq!("bright_blue".parse::<Color>().unwrap(), Color::Ansi(12));
+/// assert_eq!("256:208".parse::<Color>().unwrap(), Color::Ansi256(208));
+/// assert_eq!(
+///     "hsl(0, 100%, 50%)".parse::<Color>().unwrap(),
+///     Color::Rgb { r: 255, g: 0, b: 0 }
+/// );
+/// assert!("reddish".parse::<Color>().is_err());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Color {
+    /// One of the 16 ANSI colors, whose exact color is set by the theme of the terminal.
+    /// 0 to 7 are the normal colors and 8 to 15 are the bright colors. Larger indexes wrap
+    /// around to the 16 colors.
+    Ansi(u8),
+
+    /// A color from the xterm 256 color palette.
+    Ansi256(u8),
+
+    /// A 24-bit color.
+    Rgb {
+        /// A number between 0 and 255 which represents Red spectrum.
+        r: u8,
+        /// A number between 0 and 255 which represents Green spectrum.
+        g: u8,
+        /// A number between 0 and 255 which represents Blue spectrum.
+        b: u8,
+    },
 }
 
+/// The names of the 16 ANSI colors, in the order of their indexes.
+const ANSI_NAMES: [&str; 16] = [
+    "black",
+    "red",
+    "green",
+    "yellow",
+    "blue",
+    "magenta",
+    "cyan",
+    "white",
+    "bright_black",
+    "bright_red",
+    "bright_green",
+    "bright_yellow",
+    "bright_blue",
+    "bright_magenta",
+    "bright_cyan",
+    "bright_white",
+];
+
 impl Color {
-    /// Creates a new instance of Color with the hex code
+    /// Creates a new instance of Color from any of the notations that [`Color`] accepts.
     /// ```rust
     /// use markterm::Color;
     /// let white = Color::new("#FFF");
-    /// assert_eq!(white.r, 255)
+    /// assert_eq!(white, Color::Rgb { r: 255, g: 255, b: 255 });
+    ///
+    /// let red = Color::new("red");
+    /// assert_eq!(red, Color::Ansi(1));
     /// ```
-    pub fn new(hex_color: &str) -> Self {
+    ///
+    /// ### Panics
+    /// Panics if the color is not valid. Use [`str::parse`] for colors that are not known
+    /// to be valid, such as the ones read from a configuration file.
+    pub fn new(color: &str) -> Self {
+        match color.parse() {
+            Ok(color) => color,
+            Err(e) => panic!("{e}"),
+        }
+    }
+
+    /// Creates a new instance of Color with the hex code, or returns an error if
+    /// the hex code is not valid.
+    /// ```rust
+    /// use markterm::Color;
+    /// let white = Color::from_hex("#FFF").unwrap();
+    /// assert_eq!(white.to_rgb(), (255, 255, 255));
+    ///
+    /// assert!(Color::from_hex("#GGG").is_err());
+    /// ```
+    pub fn from_hex(hex_color: &str) -> Result<Self, Error> {
+        let invalid_color = || Error::InvalidColor(hex_color.to_string());
+
         let mut color_code = hex_color.to_string();
 
         color_code.retain(|c| !r#"#"#.contains(c));
 
+        if !color_code.is_ascii() {
+            return Err(invalid_color());
+        }
+
         if color_code.len() == 3 {
             let r = format!("{}{}", &color_code[0..1], &color_code[0..1]);
             let g = format!("{}{}", &color_code[1..2], &color_code[1..2]);
@@ -29,29 +118,78 @@ impl Color {
             let g = u8::from_str_radix(&g, 16);
             let b = u8::from_str_radix(&b, 16);
 
-            let (r, g, b) = match (r, g, b) {
-                (Ok(r_val), Ok(g_val), Ok(b_val)) => (r_val, g_val, b_val),
-                _ => {
-                    panic!("Invalid Hex color code {hex_color}");
-                }
-            };
-
-            Self { r, g, b }
+            match (r, g, b) {
+                (Ok(r), Ok(g), Ok(b)) => Ok(Self::Rgb { r, g, b }),
+                _ => Err(invalid_color()),
+            }
         } else if color_code.len() == 6 {
             let r = u8::from_str_radix(&color_code[0..2], 16);
             let g = u8::from_str_radix(&color_code[2..4], 16);
             let b = u8::from_str_radix(&color_code[4..6], 16);
 
-            let (r, g, b) = match (r, g, b) {
-                (Ok(r), Ok(g), Ok(b)) => (r, g, b),
-                _ => {
-                    panic!("Invalid Hex color code {hex_color}");
-                }
-            };
-
-            Self { r, g, b }
+            match (r, g, b) {
+                (Ok(r), Ok(g), Ok(b)) => Ok(Self::Rgb { r, g, b }),
+                _ => Err(invalid_color()),
+            }
         } else {
-            panic!("Invalid length of codes");
+            Err(invalid_color())
+        }
+    }
+
+    /// Creates a color from a hue in degrees, and a saturation and lightness between
+    /// 0 and 100.
+    /// ```rust
+    /// use markterm::Color;
+    ///
+    /// assert_eq!(Color::from_hsl(120.0, 100.0, 25.0).to_rgb(), (0, 128, 0));
+    /// ```
+    pub fn from_hsl(hue: f64, saturation: f64, lightness: f64) -> Self {
+        let hue = hue.rem_euclid(360.0) / 60.0;
+        let saturation = (saturation / 100.0).clamp(0.0, 1.0);
+        let lightness = (lightness / 100.0).clamp(0.0, 1.0);
+
+        let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+        let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+        let (r, g, b) = match hue as u8 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+
+        let m = lightness - chroma / 2.0;
+        let channel = |value: f64| ((value + m) * 255.0).round() as u8;
+        Self::Rgb {
+            r: channel(r),
+            g: channel(g),
+            b: channel(b),
+        }
+    }
+
+    /// Gets the red, green and blue of the color. The colors of the palette of the
+    /// terminal are the ones that xterm uses, as the actual colors are not known.
+    /// ```rust
+    /// use markterm::Color;
+    ///
+    /// assert_eq!(Color::Ansi(9).to_rgb(), (255, 0, 0));
+    /// assert_eq!(Color::Ansi256(208).to_rgb(), (255, 135, 0));
+    /// ```
+    pub fn to_rgb(&self) -> (u8, u8, u8) {
+        match *self {
+            Color::Rgb { r, g, b } => (r, g, b),
+            Color::Ansi(index) => ANSI_16[index as usize % 16],
+            Color::Ansi256(index @ 0..=15) => ANSI_16[index as usize],
+            Color::Ansi256(index @ 16..=231) => {
+                let index = index - 16;
+                let level = |value: u8| CUBE_LEVELS[value as usize];
+                (level(index / 36), level(index / 6 % 6), level(index % 6))
+            }
+            Color::Ansi256(index) => {
+                let level = 8 + 10 * (index - 232);
+                (level, level, level)
+            }
         }
     }
 
@@ -64,7 +202,287 @@ impl Color {
     /// assert_eq!("255;255;255", white.rgb());
     /// ```
     pub fn rgb(&self) -> String {
-        format!("{};{};{}", &self.r, &self.g, &self.b)
+        let (r, g, b) = self.to_rgb();
+        format!("{r};{g};{b}")
+    }
+
+    /// Gets the index of the closest color in the xterm 256 color palette.
+    ///
+    /// Only the 6x6x6 color cube and the grayscale ramp are used for RGB colors, as the
+    /// first 16 colors are changed by most terminal themes.
+    /// ### Example
+    /// ```rust
+    /// use markterm::Color;
+    ///
+    /// assert_eq!(Color::new("#FF8700").to_ansi256(), 208);
+    /// assert_eq!(Color::new("#808080").to_ansi256(), 244);
+    /// assert_eq!(Color::new("cyan").to_ansi256(), 6);
+    /// ```
+    pub fn to_ansi256(&self) -> u8 {
+        let (r, g, b) = match *self {
+            Color::Ansi(index) => return index % 16,
+            Color::Ansi256(index) => return index,
+            Color::Rgb { r, g, b } => (r, g, b),
+        };
+
+        let cube_index = |value: u8| match value {
+            0..=47 => 0,
+            48..=114 => 1,
+            _ => (value - 35) / 40,
+        };
+
+        let (r_index, g_index, b_index) = (cube_index(r), cube_index(g), cube_index(b));
+        let cube = (
+            CUBE_LEVELS[r_index as usize],
+            CUBE_LEVELS[g_index as usize],
+            CUBE_LEVELS[b_index as usize],
+        );
+
+        let average = (r as u32 + g as u32 + b as u32) / 3;
+        let gray_index = (average.saturating_sub(3) / 10).min(23) as u8;
+        let gray_level = 8 + 10 * gray_index;
+        let gray = (gray_level, gray_level, gray_level);
+
+        if distance((r, g, b), gray) < distance((r, g, b), cube) {
+            232 + gray_index
+        } else {
+            16 + 36 * r_index + 6 * g_index + b_index
+        }
+    }
+
+    /// Gets the index of the closest of the 16 ANSI colors. Indexes 8 to 15 are the
+    /// bright colors.
+    ///
+    /// Grays are matched by how light they are. Other colors are matched by which of red,
+    /// green and blue they are made of, as the exact colors depend on the theme of the
+    /// terminal.
+    /// ### Example
+    /// ```rust
+    /// use markterm::Color;
+    ///
+    /// assert_eq!(Color::new("#800").to_ansi16(), 1);
+    /// assert_eq!(Color::new("#5CF").to_ansi16(), 14);
+    /// assert_eq!(Color::new("256:9").to_ansi16(), 9);
+    /// ```
+    pub fn to_ansi16(&self) -> u8 {
+        let (r, g, b) = match *self {
+            Color::Ansi(index) => return index % 16,
+            Color::Ansi256(index @ 0..=15) => return index,
+            _ => self.to_rgb(),
+        };
+
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+
+        if max - min < 32 {
+            return match max {
+                0..=63 => 0,
+                64..=159 => 8,
+                160..=239 => 7,
+                _ => 15,
+            };
+        }
+
+        let is_set = |value: u8| u8::from(value >= 128);
+        let index = is_set(b) << 2 | is_set(g) << 1 | is_set(r);
+        match max {
+            // Colors that are too dark to have any of red, green or blue set are black.
+            0..=127 if index == 0 => 0,
+            0..=191 => index,
+            _ => index + 8,
+        }
+    }
+
+    /// Gets the SGR parameters that set the foreground, or the background, to this color.
+    ///
+    /// ANSI colors are written as they are, so that they follow the theme of the terminal.
+    pub(crate) fn sgr(&self, is_background: bool, depth: ColorDepth) -> String {
+        let layer = if is_background { T_BG } else { T_FG };
+        match (self, depth) {
+            (Color::Rgb { .. }, ColorDepth::TrueColor) => format!("{layer};2;{}", self.rgb()),
+            (Color::Ansi(_), _) | (_, ColorDepth::Ansi16) => {
+                let index = self.to_ansi16();
+                let base = match (is_background, index < 8) {
+                    (false, true) => 30,
+                    (false, false) => 90 - 8,
+                    (true, true) => 40,
+                    (true, false) => 100 - 8,
+                };
+
+                (base + index).to_string()
+            }
+            _ => format!("{layer};5;{}", self.to_ansi256()),
+        }
+    }
+}
+
+impl FromStr for Color {
+    type Err = Error;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let invalid_color = || Error::InvalidColor(text.to_string());
+        let color = text.trim().to_ascii_lowercase();
+
+        if let Some(index) = ANSI_NAMES.iter().position(|name| *name == color) {
+            return Ok(Color::Ansi(index as u8));
+        }
+
+        if let Some(index) = color.strip_prefix("ansi:") {
+            return match index.trim().parse() {
+                Ok(index @ 0..=15) => Ok(Color::Ansi(index)),
+                _ => Err(invalid_color()),
+            };
+        }
+
+        if let Some(index) = color.strip_prefix("256:") {
+            return index
+                .trim()
+                .parse()
+                .map(Color::Ansi256)
+                .map_err(|_| invalid_color());
+        }
+
+        if let Some(values) = function_args(&color, "rgb") {
+            return match values.as_slice() {
+                [r, g, b] => match (r.parse(), g.parse(), b.parse()) {
+                    (Ok(r), Ok(g), Ok(b)) => Ok(Color::Rgb { r, g, b }),
+                    _ => Err(invalid_color()),
+                },
+                _ => Err(invalid_color()),
+            };
+        }
+
+        if let Some(values) = function_args(&color, "hsl") {
+            let percent = |value: &str| value.strip_suffix('%').unwrap_or(value).parse::<f64>();
+            return match values.as_slice() {
+                [h, s, l] => match (
+                    h.strip_suffix("deg").unwrap_or(h).parse(),
+                    percent(s),
+                    percent(l),
+                ) {
+                    (Ok(h), Ok(s), Ok(l))
+                        if (0.0..=100.0).contains(&s) && (0.0..=100.0).contains(&l) =>
+                    {
+                        Ok(Color::from_hsl(h, s, l))
+                    }
+                    _ => Err(invalid_color()),
+                },
+                _ => Err(invalid_color()),
+            };
+        }
+
+        Color::from_hex(&color).map_err(|_| invalid_color())
+    }
+}
+
+/// Gets the arguments of a color written like a function call. e.g. `rgb(1, 2, 3)`.
+fn function_args<'a>(color: &'a str, name: &str) -> Option<Vec<&'a str>> {
+    let args = color.strip_prefix(name)?.trim_start();
+    let args = args.strip_prefix('(')?.strip_suffix(')')?;
+    Some(args.split(',').map(str::trim).collect())
+}
+
+/// Colors are written in the notation they are read in, except for hex codes which are
+/// always written with 6 digits. e.g. `bright_blue`, `256:208` or `#FF6060`.
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Color::Ansi(index) => match ANSI_NAMES.get(index as usize) {
+                Some(name) => write!(f, "{name}"),
+                None => write!(f, "ansi:{index}"),
+            },
+            Color::Ansi256(index) => write!(f, "256:{index}"),
+            Color::Rgb { r, g, b } => write!(f, "#{r:02X}{g:02X}{b:02X}"),
+        }
+    }
+}
+
+/// Gets the squared distance between the colors, weighted by how sensitive the eye
+/// is to each of red, green and blue.
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let diff = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+    2 * diff(a.0, b.0) + 4 * diff(a.1, b.1) + 3 * diff(a.2, b.2)
+}
+
+/// The number of colors the terminal can display.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum ColorDepth {
+    /// 24-bit RGB colors.
+    #[default]
+    TrueColor,
+
+    /// The xterm 256 color palette.
+    Ansi256,
+
+    /// The 16 ANSI colors, whose exact colors depend on the theme of the terminal.
+    Ansi16,
+}
+
+impl ColorDepth {
+    /// Detects the colors supported by the terminal from the `COLORTERM` and `TERM`
+    /// environment variables.
+    pub fn detect() -> Self {
+        let color_term = std::env::var("COLORTERM").unwrap_or_default();
+        let term = std::env::var("TERM").ok();
+        Self::from_env(&color_term, term.as_deref())
+    }
+
+    fn from_env(color_term: &str, term: Option<&str>) -> Self {
+        if color_term == "truecolor" || color_term == "24bit" {
+            return ColorDepth::TrueColor;
+        }
+
+        match term {
+            Some(term) if term.ends_with("-direct") => ColorDepth::TrueColor,
+            Some(term) if term.contains("256color") => ColorDepth::Ansi256,
+            Some(_) => ColorDepth::Ansi16,
+            // Windows terminals do not set `TERM`, and support 24-bit colors.
+            None if cfg!(windows) => ColorDepth::TrueColor,
+            None => ColorDepth::Ansi16,
+        }
+    }
+}
+
+const T_FG: &str = "38";
+const T_BG: &str = "48";
+
+/// The levels of each of red, green and blue in the xterm 6x6x6 color cube.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// The 16 ANSI colors as they are displayed by xterm.
+const ANSI_16: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// Colors are written to theme files in any of the notations that [`Color`] accepts.
+/// e.g. `#FF6060`, `bright_blue` or `256:208`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Color {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Color {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let color = String::deserialize(deserializer)?;
+        color.parse().map_err(serde::de::Error::custom)
     }
 }
 
@@ -103,9 +521,7 @@ mod tests {
                     let (input, r, g, b) = $value;
                     let color = Color::new(input);
 
-                    assert_eq!(color.r, r);
-                    assert_eq!(color.g, g);
-                    assert_eq!(color.b, b);
+                    assert_eq!(color, Color::Rgb { r, g, b });
                 }
             )*
         }
@@ -140,10 +556,162 @@ mod tests {
         should_fail_for_invalid_rgb_code_in_blue: "#00AAZZ",
     }
 
+    macro_rules! fallible_invalid_input_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let result = Color::from_hex($value);
+                assert!(matches!(result, Err(crate::Error::InvalidColor(color)) if color == $value));
+            }
+        )*
+        }
+    }
+
+    fallible_invalid_input_tests! {
+        from_hex_should_fail_for_2_characters: "#00",
+        from_hex_should_fail_for_4_characters: "#0000",
+        from_hex_should_fail_for_7_characters: "#0000000",
+        from_hex_should_fail_for_invalid_rgb_code: "#GG0011",
+        from_hex_should_fail_for_non_ascii_code: "#ÿÿ",
+    }
+
     valid_input_tests! {
         input_codes_can_be_3_digits: "000",
         input_codes_can_be_3_digits_with_hash: "#000",
         input_codes_can_be_6_digits: "000000",
         input_codes_can_be_6_digits_with_hash: "#000000",
     }
+
+    macro_rules! palette_tests {
+        ($($name:ident: ($value:expr, $ansi256:expr, $ansi16:expr),)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let color = Color::new($value);
+                assert_eq!(color.to_ansi256(), $ansi256, "256 color index of {}", $value);
+                assert_eq!(color.to_ansi16(), $ansi16, "16 color index of {}", $value);
+            }
+        )*
+        }
+    }
+
+    palette_tests! {
+        should_map_black_to_palette: ("#000", 16, 0),
+        should_map_white_to_palette: ("#FFF", 231, 15),
+        should_map_red_to_palette: ("#F00", 196, 9),
+        should_map_dark_red_to_palette: ("#A00", 124, 1),
+        should_map_pink_to_palette: ("#FF6060", 203, 9),
+        should_map_blue_to_palette: ("#00F", 21, 12),
+        should_map_orange_to_palette: ("#FF8700", 208, 11),
+        should_map_gray_to_grayscale_ramp: ("#808080", 244, 8),
+        should_map_dim_gray_to_grayscale_ramp: ("#555", 240, 8),
+        should_map_dark_gray_to_grayscale_ramp: ("#303030", 236, 0),
+        should_map_light_gray_to_grayscale_ramp: ("#E4E4E4", 254, 7),
+        should_map_teal_to_palette: ("#008787", 30, 6),
+    }
+
+    #[test]
+    fn should_write_sgr_parameters_for_depth() {
+        let color = Color::new("#F00");
+
+        assert_eq!(color.sgr(false, ColorDepth::TrueColor), "38;2;255;0;0");
+        assert_eq!(color.sgr(true, ColorDepth::Ansi256), "48;5;196");
+        assert_eq!(color.sgr(false, ColorDepth::Ansi16), "91");
+        assert_eq!(Color::new("#A00").sgr(true, ColorDepth::Ansi16), "41");
+        assert_eq!(Color::new("#FFF").sgr(true, ColorDepth::Ansi16), "107");
+    }
+
+    #[test]
+    fn should_detect_color_depth_from_environment() {
+        let detect = ColorDepth::from_env;
+
+        assert_eq!(detect("truecolor", Some("xterm")), ColorDepth::TrueColor);
+        assert_eq!(detect("24bit", None), ColorDepth::TrueColor);
+        assert_eq!(detect("", Some("xterm-direct")), ColorDepth::TrueColor);
+        assert_eq!(detect("", Some("tmux-256color")), ColorDepth::Ansi256);
+        assert_eq!(detect("", Some("xterm")), ColorDepth::Ansi16);
+        assert_eq!(detect("", Some("dumb")), ColorDepth::Ansi16);
+    }
+
+    macro_rules! parse_tests {
+        ($($name:ident: ($value:expr, $expected:expr),)*) => {
+        $(
+            #[test]
+            fn $name() {
+                assert_eq!($value.parse::<Color>().unwrap(), $expected);
+            }
+        )*
+        }
+    }
+
+    parse_tests! {
+        should_parse_ansi_names: ("magenta", Color::Ansi(5)),
+        should_parse_bright_ansi_names: ("Bright_Blue", Color::Ansi(12)),
+        should_parse_ansi_indexes: ("ansi:12", Color::Ansi(12)),
+        should_parse_256_color_indexes: ("256:208", Color::Ansi256(208)),
+        should_parse_rgb_function: ("rgb(255, 96, 0)", Color::Rgb { r: 255, g: 96, b: 0 }),
+        should_parse_hsl_function: ("hsl(240, 100%, 50%)", Color::Rgb { r: 0, g: 0, b: 255 }),
+        should_parse_hsl_gray: ("hsl(90deg, 0%, 50%)", Color::Rgb { r: 128, g: 128, b: 128 }),
+        should_parse_hex_codes: (" #FF6060 ", Color::Rgb { r: 255, g: 96, b: 96 }),
+    }
+
+    macro_rules! parse_error_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let result = $value.parse::<Color>();
+                assert!(matches!(result, Err(crate::Error::InvalidColor(color)) if color == $value));
+            }
+        )*
+        }
+    }
+
+    parse_error_tests! {
+        should_not_parse_unknown_names: "reddish",
+        should_not_parse_ansi_indexes_above_15: "ansi:16",
+        should_not_parse_256_color_indexes_above_255: "256:256",
+        should_not_parse_rgb_with_two_values: "rgb(1, 2)",
+        should_not_parse_rgb_values_above_255: "rgb(256, 0, 0)",
+        should_not_parse_hsl_saturation_above_100: "hsl(0, 120%, 50%)",
+        should_not_parse_unclosed_functions: "rgb(1, 2, 3",
+    }
+
+    #[test]
+    fn should_display_colors_in_the_notation_they_are_parsed_from() {
+        for color in ["bright_cyan", "256:42", "#FF6060"] {
+            assert_eq!(Color::new(color).to_string(), color);
+        }
+    }
+
+    #[test]
+    fn should_write_palette_colors_as_palette_indexes() {
+        let ansi = Color::new("red");
+        let xterm = Color::new("256:208");
+
+        assert_eq!(ansi.sgr(false, ColorDepth::TrueColor), "31");
+        assert_eq!(ansi.sgr(true, ColorDepth::Ansi256), "41");
+        assert_eq!(
+            Color::new("bright_white").sgr(false, ColorDepth::TrueColor),
+            "97"
+        );
+        assert_eq!(xterm.sgr(false, ColorDepth::TrueColor), "38;5;208");
+        assert_eq!(xterm.sgr(true, ColorDepth::Ansi16), "103");
+    }
+
+    #[test]
+    fn should_wrap_ansi_indexes_out_of_range() {
+        assert_eq!(Color::Ansi(20).to_ansi16(), 4);
+        assert_eq!(Color::Ansi(20).sgr(false, ColorDepth::TrueColor), "34");
+        assert_eq!(Color::Ansi(255).sgr(true, ColorDepth::Ansi16), "107");
+    }
+
+    #[test]
+    fn should_convert_palette_colors_to_rgb() {
+        assert_eq!(Color::Ansi256(16).to_rgb(), (0, 0, 0));
+        assert_eq!(Color::Ansi256(231).to_rgb(), (255, 255, 255));
+        assert_eq!(Color::Ansi256(244).to_rgb(), (128, 128, 128));
+        assert_eq!(Color::Ansi256(4).to_rgb(), (0, 0, 238));
+    }
 }
@@ -0,0 +1,402 @@
+use super::{
+    get_dark_theme, get_default_theme, get_light_theme, ElementTheme, HeadingFormat, NumberFormat,
+    Theme,
+};
+use crate::Error;
+use serde::Deserialize;
+use std::path::Path;
+
+/// The formats that themes can be read from.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ThemeFormat {
+    /// TOML. Needs the `toml` feature.
+    Toml,
+
+    /// JSON. Needs the `json` feature.
+    Json,
+
+    /// YAML. Needs the `yaml` feature.
+    Yaml,
+}
+
+impl ThemeFormat {
+    /// Gets the format from the extension of the file.
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        let extension = path.as_ref().extension()?.to_str()?;
+        match extension.to_ascii_lowercase().as_str() {
+            "toml" => Some(ThemeFormat::Toml),
+            "json" => Some(ThemeFormat::Json),
+            "yaml" | "yml" => Some(ThemeFormat::Yaml),
+            _ => None,
+        }
+    }
+
+    fn feature(&self) -> &str {
+        match self {
+            ThemeFormat::Toml => "toml",
+            ThemeFormat::Json => "json",
+            ThemeFormat::Yaml => "yaml",
+        }
+    }
+}
+
+/// The built in themes that a theme file can start from.
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum BaseTheme {
+    Dark,
+    Light,
+}
+
+/// Creates the struct that a theme file is read into, where every key is optional, and
+/// the code that copies the keys that are set onto a theme.
+///
+/// The elements in `nested` are read from theme files of their own and are copied key by
+/// key. The keys in `settings` change how the file is read and are not copied.
+macro_rules! theme_file {
+    (
+        $name:ident for $theme:ty {
+            $($field:ident: $type:ty,)*
+        }
+        $(nested { $($nested:ident: $nested_type:ty,)* })?
+        $(settings { $($setting:ident: $setting_type:ty,)* })?
+    ) => {
+        #[derive(Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct $name {
+            $($field: Option<$type>,)*
+            $($($nested: Option<$nested_type>,)*)?
+            $($($setting: Option<$setting_type>,)*)?
+        }
+
+        impl $name {
+            fn apply_to(self, theme: &mut $theme) {
+                $(
+                    if let Some(value) = self.$field {
+                        theme.$field = value;
+                    }
+                )*
+                $($(
+                    if let Some(value) = self.$nested {
+                        value.apply_to(&mut theme.$nested);
+                    }
+                )*)?
+            }
+        }
+    };
+}
+
+theme_file!(SyntaxThemeFile for super::SyntaxTheme {
+    comment: ElementTheme,
+    keyword: ElementTheme,
+    string: ElementTheme,
+    number: ElementTheme,
+    function: ElementTheme,
+    type_name: ElementTheme,
+});
+
+theme_file!(ThemeFile for Theme {
+    header_1: ElementTheme,
+    header_2: ElementTheme,
+    header_3: ElementTheme,
+    header_4: ElementTheme,
+    header_5: ElementTheme,
+    header_6: ElementTheme,
+    header_formats: Vec<HeadingFormat>,
+    code_inline: ElementTheme,
+    code_block: ElementTheme,
+    code_block_label: ElementTheme,
+    code_block_gutter: ElementTheme,
+    code_block_show_label: bool,
+    code_block_show_line_numbers: bool,
+    code_block_margin: usize,
+    indents: ElementTheme,
+    indent_bars: Vec<char>,
+    indent_levels: Vec<ElementTheme>,
+    alert_note: ElementTheme,
+    alert_tip: ElementTheme,
+    alert_important: ElementTheme,
+    alert_warning: ElementTheme,
+    alert_caution: ElementTheme,
+    link: ElementTheme,
+    image: ElementTheme,
+    footnote: ElementTheme,
+    footnote_superscripts: bool,
+    list: ElementTheme,
+    list_bullets: Vec<char>,
+    list_number_formats: Vec<NumberFormat>,
+    list_indent: usize,
+    task_checked: ElementTheme,
+    task_unchecked: ElementTheme,
+    strong: ElementTheme,
+    emphasis: ElementTheme,
+    delete: ElementTheme,
+    table_header: ElementTheme,
+    table_border: ElementTheme,
+    html_kbd: ElementTheme,
+    html_summary: ElementTheme,
+    html_tag: ElementTheme,
+    frontmatter: ElementTheme,
+    frontmatter_banner: ElementTheme,
+    math: ElementTheme,
+    rule: ElementTheme,
+    rule_char: char,
+}
+nested {
+    syntax: SyntaxThemeFile,
+}
+settings {
+    base: BaseTheme,
+});
+
+impl Theme {
+    /// Reads a theme from the text.
+    ///
+    /// Only the elements that are changed need to be set. The rest are taken from the
+    /// theme named by the `base` key, which is either `dark` or `light`, or from
+    /// [`get_default_theme`] when it is left out. An element that is set replaces the
+    /// whole element of the base theme.
+    ///
+    /// Returns an error that names the key if the theme is not valid.
+    ///
+    /// ### Example
+    /// ```rust
+    /// # #[cfg(feature = "toml")]
+    /// # {
+    /// use markterm::{Color, TextStyle, Theme, ThemeFormat};
+    ///
+    /// let text = r##"
+    /// base = "dark"
+    /// header_1 = { fg = "#FFF", bg = "#500", style = "bold" }
+    /// "##;
+    ///
+    /// let theme = Theme::from_str(text, ThemeFormat::Toml).unwrap();
+    /// assert_eq!(theme.header_1.bg, Some(Color::new("#500")));
+    /// assert_eq!(theme.link, markterm::themes::get_dark_theme().link);
+    ///
+    /// let error = Theme::from_str("header_1 = { fg = \"#GGG\" }", ThemeFormat::Toml)
+    ///     .unwrap_err()
+    ///     .to_string();
+    /// assert!(error.starts_with("Invalid theme at `header_1.fg`"));
+    /// assert!(error.contains("Invalid color `#GGG`"));
+    /// # }
+    /// ```
+    pub fn from_str(text: &str, format: ThemeFormat) -> Result<Theme, Error> {
+        let file = parse(text, format)?;
+        let mut theme = match file.base {
+            Some(BaseTheme::Dark) => get_dark_theme(),
+            Some(BaseTheme::Light) => get_light_theme(),
+            None => get_default_theme(),
+        };
+
+        file.apply_to(&mut theme);
+        Ok(theme)
+    }
+
+    /// Reads a theme from the file. The format is taken from the extension of the file,
+    /// which is one of `.toml`, `.json`, `.yaml` or `.yml`.
+    ///
+    /// See [`Theme::from_str`] for how the file is read.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Theme, Error> {
+        let (text, format) = read(path.as_ref())?;
+        Theme::from_str(&text, format)
+    }
+
+    /// Replaces the elements of the theme with the ones that are set in the text.
+    /// This lets users override some of the elements of a theme shipped with a tool.
+    ///
+    /// The `base` key is ignored, as this theme is the base.
+    pub fn merge_str(mut self, text: &str, format: ThemeFormat) -> Result<Theme, Error> {
+        parse(text, format)?.apply_to(&mut self);
+        Ok(self)
+    }
+
+    /// Replaces the elements of the theme with the ones that are set in the file.
+    ///
+    /// See [`Theme::from_path`] and [`Theme::merge_str`].
+    pub fn merge_path(self, path: impl AsRef<Path>) -> Result<Theme, Error> {
+        let (text, format) = read(path.as_ref())?;
+        self.merge_str(&text, format)
+    }
+}
+
+fn read(path: &Path) -> Result<(String, ThemeFormat), Error> {
+    let format = ThemeFormat::from_path(path).ok_or_else(|| Error::InvalidTheme {
+        key: None,
+        reason: format!(
+            "The format of {} is not known. Use a .toml, .json or .yaml file",
+            path.display()
+        ),
+    })?;
+
+    let text = std::fs::read(path)?;
+    Ok((String::from_utf8(text).map_err(|e| e.utf8_error())?, format))
+}
+
+#[cfg_attr(
+    not(any(feature = "toml", feature = "json", feature = "yaml")),
+    allow(unused_variables)
+)]
+fn parse(text: &str, format: ThemeFormat) -> Result<ThemeFile, Error> {
+    match format {
+        #[cfg(feature = "toml")]
+        ThemeFormat::Toml => deserialize(toml::Deserializer::new(text)),
+        #[cfg(feature = "json")]
+        ThemeFormat::Json => {
+            let mut deserializer = serde_json::Deserializer::from_str(text);
+            let file = deserialize(&mut deserializer)?;
+            deserializer.end().map_err(|e| Error::InvalidTheme {
+                key: None,
+                reason: e.to_string(),
+            })?;
+
+            Ok(file)
+        }
+        #[cfg(feature = "yaml")]
+        ThemeFormat::Yaml => deserialize(serde_yaml::Deserializer::from_str(text)),
+        #[allow(unreachable_patterns)]
+        format => Err(Error::InvalidTheme {
+            key: None,
+            reason: format!(
+                "Reading {format:?} themes needs the `{}` feature",
+                format.feature()
+            ),
+        }),
+    }
+}
+
+/// Reads the theme file, keeping track of the key that is being read so that it can be
+/// named in the error.
+#[cfg_attr(
+    not(any(feature = "toml", feature = "json", feature = "yaml")),
+    allow(dead_code)
+)]
+fn deserialize<'de, D>(deserializer: D) -> Result<ThemeFile, Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    serde_path_to_error::deserialize(deserializer).map_err(|e| {
+        let key = e.path().to_string();
+        Error::InvalidTheme {
+            key: (key != ".").then_some(key),
+            reason: e.into_inner().to_string(),
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[cfg(any(feature = "toml", feature = "json", feature = "yaml"))]
+    use crate::{Color, TextStyle};
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn should_read_partial_toml_theme() {
+        let text = r##"
+            base = "light"
+            list_bullets = ["*", "-"]
+
+            [strong]
+            fg = "#F00"
+            style = "underlined"
+
+            [syntax.keyword]
+            fg = "#00F"
+        "##;
+
+        let theme = Theme::from_str(text, ThemeFormat::Toml).unwrap();
+        let light = get_light_theme();
+
+        assert_eq!(
+            theme.strong,
+            ElementTheme::new(Some("#F00"), None, TextStyle::Underlined)
+        );
+        assert_eq!(theme.list_bullets, vec!['*', '-']);
+        assert_eq!(theme.syntax.keyword.fg, Some(Color::new("#00F")));
+        assert_eq!(theme.syntax.string, light.syntax.string);
+        assert_eq!(theme.header_1, light.header_1);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn should_read_json_theme() {
+        let text = r##"{
+            "base": "dark",
+            "link": { "fg": "#123456" },
+            "strong": { "style": ["bold", "curly_underlined"] },
+            "list_indent": 4
+        }"##;
+        let theme = Theme::from_str(text, ThemeFormat::Json).unwrap();
+
+        assert_eq!(theme.link.fg, Some(Color::new("#123456")));
+        assert_eq!(theme.link.style, TextStyle::Normal);
+        assert_eq!(
+            theme.strong.style,
+            TextStyle::Bold | TextStyle::CurlyUnderlined
+        );
+        assert_eq!(theme.list_indent, 4);
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn should_read_palette_colors() {
+        let text = r#"
+            link = { fg = "bright_blue" }
+            strong = { fg = "256:208", bg = "rgb(0, 0, 48)" }
+        "#;
+        let theme = Theme::from_str(text, ThemeFormat::Toml).unwrap();
+
+        assert_eq!(theme.link.fg, Some(Color::Ansi(12)));
+        assert_eq!(theme.strong.fg, Some(Color::Ansi256(208)));
+        assert_eq!(theme.strong.bg, Some(Color::Rgb { r: 0, g: 0, b: 48 }));
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn should_read_yaml_theme() {
+        let text = "base: dark\nlist_number_formats:\n  - style: upper_roman\n    suffix: ')'\n";
+        let theme = Theme::from_str(text, ThemeFormat::Yaml).unwrap();
+
+        assert_eq!(theme.list_number_formats[0].format(4), "IV)");
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn should_name_the_key_that_is_not_valid() {
+        let text = r##"{ "syntax": { "comment": { "fg": "#12" } } }"##;
+        let error = Theme::from_str(text, ThemeFormat::Json).unwrap_err();
+
+        assert!(matches!(
+            &error,
+            Error::InvalidTheme { key: Some(key), .. } if key == "syntax.comment.fg"
+        ));
+        assert!(error.to_string().contains("Invalid color `#12`"));
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn should_reject_unknown_keys() {
+        let text = "base: dark\nheader_7:\n  fg: '#FFF'\n";
+        let error = Theme::from_str(text, ThemeFormat::Yaml).unwrap_err();
+
+        assert!(error.to_string().contains("unknown field `header_7`"));
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn should_merge_into_theme() {
+        let theme = get_dark_theme()
+            .merge_str("emphasis = { style = \"bold\" }", ThemeFormat::Toml)
+            .unwrap();
+
+        assert_eq!(theme.emphasis.style, TextStyle::Bold);
+        assert_eq!(theme.header_1, get_dark_theme().header_1);
+    }
+
+    #[test]
+    fn should_detect_format_from_extension() {
+        assert_eq!(ThemeFormat::from_path("theme.YML"), Some(ThemeFormat::Yaml));
+        assert_eq!(ThemeFormat::from_path("theme.txt"), None);
+    }
+}
@@ -0,0 +1,202 @@
+use std::fmt;
+use std::ops::{BitOr, BitOrAssign};
+
+/// The attributes of text such as bold, italics and underlined.
+///
+/// Attributes are combined with `|`. e.g. `TextStyle::Bold | TextStyle::Underlined`.
+/// When an element is written inside another element, such as `***text***`, the
+/// attributes of both elements are applied.
+///
+/// ### Example
+/// ```rust
+/// use markterm::TextStyle;
+///
+/// let style = TextStyle::Bold | TextStyle::Italics;
+/// assert!(style.contains(TextStyle::Bold));
+/// assert!(!style.contains(TextStyle::Underlined));
+/// assert_eq!(style.style_key(), "1;3");
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct TextStyle(u16);
+
+#[allow(non_upper_case_globals)]
+impl TextStyle {
+    /// Indicates normal text, without any attributes.
+    pub const Normal: TextStyle = TextStyle(0);
+
+    /// Indicates **bold text**.
+    pub const Bold: TextStyle = TextStyle(1);
+
+    /// Indicates dim or faint text.
+    pub const Dim: TextStyle = TextStyle(1 << 1);
+
+    /// Indicates *italicised text*.
+    pub const Italics: TextStyle = TextStyle(1 << 2);
+
+    /// Indicates <ins>underlined text</ins>.
+    pub const Underlined: TextStyle = TextStyle(1 << 3);
+
+    /// Indicates text with two lines under it.
+    pub const DoubleUnderlined: TextStyle = TextStyle(1 << 4);
+
+    /// Indicates text with a wavy line under it, as used for spelling mistakes.
+    pub const CurlyUnderlined: TextStyle = TextStyle(1 << 5);
+
+    /// Indicates blinking text.
+    pub const Blink: TextStyle = TextStyle(1 << 6);
+
+    /// Indicates text whose foreground and background colors are swapped.
+    pub const Reversed: TextStyle = TextStyle(1 << 7);
+
+    /// Indicates ~~Strikethrough text~~.
+    pub const Strikethrough: TextStyle = TextStyle(1 << 8);
+
+    /// Indicates text with a line over it.
+    pub const Overlined: TextStyle = TextStyle(1 << 9);
+}
+
+/// Every attribute with its SGR parameter and its name in theme files.
+const ATTRIBUTES: [(TextStyle, &str, &str); 10] = [
+    (TextStyle::Bold, "1", "bold"),
+    (TextStyle::Dim, "2", "dim"),
+    (TextStyle::Italics, "3", "italics"),
+    (TextStyle::Underlined, "4", "underlined"),
+    (TextStyle::DoubleUnderlined, "4:2", "double_underlined"),
+    (TextStyle::CurlyUnderlined, "4:3", "curly_underlined"),
+    (TextStyle::Blink, "5", "blink"),
+    (TextStyle::Reversed, "7", "reversed"),
+    (TextStyle::Strikethrough, "9", "strikethrough"),
+    (TextStyle::Overlined, "53", "overlined"),
+];
+
+impl TextStyle {
+    /// Indicates whether all the attributes of `other` are set.
+    pub fn contains(&self, other: TextStyle) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Indicates whether no attributes are set.
+    pub fn is_normal(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Gets the console style key for the text style without escape characters.
+    /// The parameters of the attributes are separated by `;`.
+    pub fn style_key(&self) -> String {
+        self.attributes()
+            .map(|(_, key, _)| *key)
+            .collect::<Vec<_>>()
+            .join(";")
+    }
+
+    fn attributes(&self) -> impl Iterator<Item = &(TextStyle, &'static str, &'static str)> {
+        let style = *self;
+        ATTRIBUTES
+            .iter()
+            .filter(move |(attribute, _, _)| style.contains(*attribute))
+    }
+}
+
+impl BitOr for TextStyle {
+    type Output = TextStyle;
+
+    fn bitor(self, rhs: TextStyle) -> TextStyle {
+        TextStyle(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for TextStyle {
+    fn bitor_assign(&mut self, rhs: TextStyle) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl fmt::Debug for TextStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_normal() {
+            return write!(f, "TextStyle(normal)");
+        }
+
+        let names: Vec<&str> = self.attributes().map(|(_, _, name)| *name).collect();
+        write!(f, "TextStyle({})", names.join(" | "))
+    }
+}
+
+/// Text styles are written to theme files as a list of attribute names, such as
+/// `["bold", "underlined"]`. A single name, or `normal`, can be written on its own.
+#[cfg(feature = "serde")]
+impl serde::Serialize for TextStyle {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.attributes().map(|(_, _, name)| name))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for TextStyle {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(serde::Deserialize)]
+        #[serde(untagged)]
+        enum Names {
+            One(String),
+            Many(Vec<String>),
+        }
+
+        let names = match Names::deserialize(deserializer)? {
+            Names::One(name) => vec![name],
+            Names::Many(names) => names,
+        };
+
+        let mut style = TextStyle::Normal;
+        for name in names {
+            if name == "normal" {
+                continue;
+            }
+
+            match ATTRIBUTES.iter().find(|(_, _, n)| *n == name) {
+                Some((attribute, _, _)) => style |= *attribute,
+                None => {
+                    let expected: Vec<&str> = ATTRIBUTES.iter().map(|(_, _, n)| *n).collect();
+                    return Err(serde::de::Error::custom(format!(
+                        "unknown text style `{name}`, expected normal or one of {}",
+                        expected.join(", ")
+                    )));
+                }
+            }
+        }
+
+        Ok(style)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_combine_attributes() {
+        let style = TextStyle::Bold | TextStyle::Strikethrough | TextStyle::Overlined;
+
+        assert_eq!(style.style_key(), "1;9;53");
+        assert!(style.contains(TextStyle::Bold | TextStyle::Overlined));
+        assert!(!style.contains(TextStyle::Italics));
+    }
+
+    #[test]
+    fn should_use_sub_parameters_for_underline_styles() {
+        assert_eq!(TextStyle::DoubleUnderlined.style_key(), "4:2");
+        assert_eq!(TextStyle::CurlyUnderlined.style_key(), "4:3");
+    }
+
+    #[test]
+    fn should_have_no_key_for_normal_text() {
+        assert!(TextStyle::Normal.is_normal());
+        assert_eq!(TextStyle::Normal.style_key(), "");
+    }
+
+    #[test]
+    fn should_debug_print_attributes() {
+        let style = TextStyle::Dim | TextStyle::Reversed;
+
+        assert_eq!(format!("{style:?}"), "TextStyle(dim | reversed)");
+    }
+}
@@ -1,28 +1,369 @@
-use crate::{ElementTheme, TextStyle, Theme};
+use crate::renderer::RenderOptions;
+use crate::{
+    ColorDepth, ElementTheme, Error, FootnotePlacement, FrontmatterDisplay, ImageProtocol,
+    LinkFallback, Theme, UnknownHtml,
+};
 use markdown::{self, mdast};
+use std::path::Path;
+
+/// Helpers to measure text that contains terminal escape sequences.
+mod ansi;
+
+/// Writes code blocks as panels.
+mod code;
+
+/// Numbers footnotes and writes their definitions.
+mod footnote;
+
+/// Styles the nodes in a small set of HTML tags, and strips or shows the rest.
+mod html;
+
+/// Writes the frontmatter of documents as a table or a banner.
+mod frontmatter;
+
+/// Draws images or writes placeholders for them.
+mod image;
+
+/// Renders headings with the theme and the prefix of their level.
+mod heading;
+
+/// Writes links as hyperlinks, or with their URL when hyperlinks are not supported.
+mod link;
+
+/// Writes TeX math with Unicode symbols in place of its commands.
+mod math;
+
+/// Highlights the tokens of fenced code blocks.
+#[cfg(feature = "syntax-highlighting")]
+mod highlight;
+
+/// Renders ordered and unordered lists.
+mod list;
+
+/// Renders block quotes with a bar in front of every line.
+mod quote;
+
+/// Converts text to Unicode superscript and subscript characters.
+mod script;
+
+/// Renders GFM tables.
+mod table;
+
+/// Wraps text to the width of the terminal.
+mod wrap;
+
+use footnote::Footnotes;
+use heading::HeadingNumbers;
+use link::{LinkDefinitions, LinkReferences};
 
 const T_ESC: &str = "\u{1b}";
 
-/// Writes the passed in text in markdown to the writer using the theme.
+/// The number of columns that rules span when text is not wrapped.
+const RULE_WIDTH: usize = 80;
+
+/// Writes the passed in text in markdown to the writer using the theme without wrapping it.
+#[cfg(test)]
 pub fn write(
     text: &str,
     theme: &Theme,
+    writer: impl std::io::Write,
+    is_writer_tty: bool,
+) -> Result<(), Error> {
+    write_with_options(
+        text,
+        theme,
+        &test_options(),
+        &default_parse_options(),
+        writer,
+        is_writer_tty,
+    )
+}
+
+/// Gets the options that tests write with, which do not depend on the terminal that the
+/// tests are run in.
+#[cfg(test)]
+pub fn test_options() -> RenderOptions {
+    RenderOptions {
+        width: None,
+        images: ImageProtocol::Placeholder,
+        color_depth: ColorDepth::TrueColor,
+        unicode: true,
+        ..Default::default()
+    }
+}
+
+/// Writes the markdown to a string with the options. Panics if it cannot be written, so
+/// that an error fails the test instead of cutting the output short.
+#[cfg(test)]
+pub fn render_with_options(
+    text: &str,
+    theme: &Theme,
+    options: &RenderOptions,
+    is_writer_tty: bool,
+) -> String {
+    let mut result = Vec::new();
+    write_with_options(
+        text,
+        theme,
+        options,
+        &default_parse_options(),
+        &mut result,
+        is_writer_tty,
+    )
+    .unwrap();
+
+    String::from_utf8(result).unwrap()
+}
+
+/// Gets the options used to parse markdown when none are set.
+pub fn default_parse_options() -> markdown::ParseOptions {
+    let mut options = markdown::ParseOptions::gfm();
+    options.constructs.frontmatter = true;
+    options.constructs.math_flow = true;
+    options.constructs.math_text = true;
+    options
+}
+
+/// Writes the passed in text in markdown to the writer using the theme and the options.
+pub fn write_with_options(
+    text: &str,
+    theme: &Theme,
+    options: &RenderOptions,
+    parse_options: &markdown::ParseOptions,
     mut writer: impl std::io::Write,
     is_writer_tty: bool,
-) -> Result<(), std::io::Error> {
-    let parse_options = markdown::ParseOptions::gfm();
-    let ast = match markdown::to_mdast(text, &parse_options) {
-        Ok(ast) => ast,
-        Err(e) => {
-            panic!("{}", e);
-        }
-    };
+) -> Result<(), Error> {
+    let ast = markdown::to_mdast(text, parse_options)?;
 
     if cfg!(test) {
         print_ast_json(&ast);
     }
 
-    write_colored_text(&ast, theme, &mut writer, &is_writer_tty)
+    let document = Document::default();
+    if let Some(blocks) = ast.children() {
+        document.collect_definitions(blocks);
+    }
+
+    let ctx = Context::new(theme, options, &document, is_writer_tty);
+    write_colored_text(&ast, &ctx, &mut writer)?;
+
+    let previous = ast.children().and_then(|blocks| {
+        (blocks.iter().rev()).find(|block| is_written_in_place(block, options.footnotes))
+    });
+    write_end(previous, &ctx, &mut writer)?;
+    Ok(())
+}
+
+/// Writes the top level blocks of a document that is written a few blocks at a time.
+///
+/// `previous` is the last block that was written before these blocks, and is used to
+/// separate the blocks from it in the same way as if the whole document was written at once.
+/// `document` is shared by all of the blocks of the document, so that headings and
+/// footnotes are numbered in order.
+pub fn write_top_level_blocks(
+    blocks: &[mdast::Node],
+    previous: Option<&mdast::Node>,
+    document: &Document,
+    theme: &Theme,
+    options: &RenderOptions,
+    mut writer: impl std::io::Write,
+    is_writer_tty: bool,
+) -> Result<(), Error> {
+    let ctx = Context::new(theme, options, document, is_writer_tty);
+    write_blocks_after(blocks, previous, &ctx, &mut writer)?;
+    Ok(())
+}
+
+/// Writes the footnotes and links that were kept for the end of a document that was
+/// written a few blocks at a time. `previous` is the last block that was written.
+pub fn write_end_of_document(
+    previous: Option<&mdast::Node>,
+    document: &Document,
+    theme: &Theme,
+    options: &RenderOptions,
+    mut writer: impl std::io::Write,
+    is_writer_tty: bool,
+) -> Result<(), Error> {
+    let ctx = Context::new(theme, options, document, is_writer_tty);
+    write_end(previous, &ctx, &mut writer)?;
+    Ok(())
+}
+
+/// Writes the footnotes, and then the numbered links, that were kept for the end of the
+/// document. They are separated from `previous`, the last block of the document.
+fn write_end(
+    previous: Option<&mdast::Node>,
+    ctx: &Context,
+    writer: &mut impl std::io::Write,
+) -> Result<(), std::io::Error> {
+    let separator = match previous {
+        None => "",
+        Some(mdast::Node::Paragraph(_) | mdast::Node::Html(_)) => "\n\n",
+        Some(_) => "\n",
+    };
+
+    // The footnotes can link to pages themselves, so they are written first.
+    let separator = match footnote::write_footnotes(separator, ctx, writer)? {
+        true => "\n",
+        false => separator,
+    };
+
+    link::write_link_references(separator, ctx, writer)?;
+    Ok(())
+}
+
+/// Indicates whether the block is written where it is in the document. Link definitions
+/// are never written, and footnote definitions can be written at the end of the document.
+pub fn is_written_in_place(block: &mdast::Node, footnotes: FootnotePlacement) -> bool {
+    match block {
+        mdast::Node::Definition(_) => false,
+        mdast::Node::FootnoteDefinition(_) => footnotes != FootnotePlacement::End,
+        _ => true,
+    }
+}
+
+/// The state of a document that is carried from one block to the next.
+#[derive(Debug, Default)]
+pub struct Document {
+    /// The headings that have been written, used to number the next heading.
+    headings: HeadingNumbers,
+
+    /// The footnotes that have been referenced or defined.
+    footnotes: Footnotes,
+
+    /// The links that have been written as numbered references.
+    links: LinkReferences,
+
+    /// The definitions that links and images can use.
+    definitions: LinkDefinitions,
+}
+
+impl Document {
+    /// Adds the definitions in the blocks, so that the links and images in any block of
+    /// the document can use them.
+    pub fn collect_definitions(&self, blocks: &[mdast::Node]) {
+        self.definitions.collect(blocks);
+    }
+}
+
+/// The state that is carried down the tree while the document is written.
+#[derive(Clone, Copy)]
+struct Context<'a> {
+    /// The theme used to color the elements.
+    theme: &'a Theme,
+
+    /// Indicates whether escape sequences should be written.
+    is_writer_tty: bool,
+
+    /// The number of colors that the terminal can display.
+    color_depth: ColorDepth,
+
+    /// Indicates whether links should be written as terminal hyperlinks.
+    hyperlinks: bool,
+
+    /// The protocol used to draw images.
+    images: ImageProtocol,
+
+    /// The directory that the paths of images are relative to.
+    base_dir: Option<&'a Path>,
+
+    /// The size in bytes of the largest image file that is drawn.
+    max_image_bytes: u64,
+
+    /// The headings that have been written, used to number the next heading.
+    headings: &'a HeadingNumbers,
+
+    /// The footnotes that have been referenced or defined, used to number them.
+    footnotes: &'a Footnotes,
+
+    /// Where footnote definitions are written.
+    footnote_placement: FootnotePlacement,
+
+    /// The links that have been written as numbered references, used to number them.
+    links: &'a LinkReferences,
+
+    /// How links are written when they cannot be written as hyperlinks.
+    link_fallback: LinkFallback,
+
+    /// The definitions that links and images can use.
+    definitions: &'a LinkDefinitions,
+
+    /// How HTML tags that are not known are written.
+    unknown_html: UnknownHtml,
+
+    /// How the frontmatter at the top of the document is written.
+    frontmatter: FrontmatterDisplay,
+
+    /// Indicates whether characters that are not ASCII can be written.
+    unicode: bool,
+
+    /// The combined theme of the elements that enclose the element being written.
+    style: ElementTheme,
+
+    /// The number of lists that enclose the element being written.
+    list_depth: usize,
+
+    /// The number of block quotes that enclose the element being written.
+    quote_depth: usize,
+
+    /// The number of columns available to the element being written, after the
+    /// indentation of the enclosing lists and block quotes is taken away.
+    width: Option<usize>,
+}
+
+impl<'a> Context<'a> {
+    /// Creates the context for the top level of the document.
+    fn new(
+        theme: &'a Theme,
+        options: &'a RenderOptions,
+        document: &'a Document,
+        is_writer_tty: bool,
+    ) -> Self {
+        Context {
+            theme,
+            is_writer_tty,
+            color_depth: options.color_depth,
+            hyperlinks: options.hyperlinks,
+            images: options.images,
+            base_dir: options.base_dir.as_deref(),
+            max_image_bytes: options.max_image_bytes,
+            headings: &document.headings,
+            footnotes: &document.footnotes,
+            footnote_placement: options.footnotes,
+            links: &document.links,
+            link_fallback: options.link_fallback,
+            definitions: &document.definitions,
+            unknown_html: options.unknown_html,
+            frontmatter: options.frontmatter,
+            unicode: options.unicode,
+            style: ElementTheme::default(),
+            list_depth: 0,
+            quote_depth: 0,
+            width: options.width,
+        }
+    }
+
+    /// Creates the context for the children of an element that is themed with the theme.
+    fn styled(&self, theme: &ElementTheme) -> Self {
+        Context {
+            style: self.style.combine(theme),
+            ..*self
+        }
+    }
+
+    /// Creates the context for the children of a block that indents them by the width.
+    fn indented(&self, indent: usize) -> Self {
+        Context {
+            width: self.width.map(|width| width.saturating_sub(indent)),
+            ..*self
+        }
+    }
+
+    /// Gets the colors that themes are written with, or `None` when escape sequences
+    /// should not be written.
+    fn colors(&self) -> Option<ColorDepth> {
+        self.is_writer_tty.then_some(self.color_depth)
+    }
 }
 
 #[cfg(test)]
@@ -38,18 +379,13 @@ fn print_ast_json(_ast: &mdast::Node) {}
 
 fn write_colored_text(
     node: &mdast::Node,
-    theme: &Theme,
+    ctx: &Context,
     writer: &mut impl std::io::Write,
-    is_writer_tty: &bool,
 ) -> Result<(), std::io::Error> {
+    let theme = ctx.theme;
+
     match node {
-        mdast::Node::Root(root) => write_themed_text(
-            ElementType::Nodes(&root.children),
-            theme,
-            None,
-            writer,
-            is_writer_tty,
-        ),
+        mdast::Node::Root(root) => write_blocks(&root.children, ctx, writer),
         mdast::Node::Paragraph(para) => {
             let children = &para.children;
             let mut is_code_para = false;
@@ -64,13 +400,20 @@ fn write_colored_text(
                 writeln!(writer)?;
             }
 
-            write_themed_text(
-                ElementType::Nodes(children),
-                theme,
-                None,
-                writer,
-                is_writer_tty,
-            )?;
+            match ctx.width {
+                Some(width) => {
+                    let mut write_intercept = Vec::new();
+                    write_themed_text(
+                        ElementType::Nodes(children),
+                        ctx,
+                        None,
+                        &mut write_intercept,
+                    )?;
+                    let text = String::from_utf8_lossy(&write_intercept);
+                    write!(writer, "{}", wrap::wrap(&text, width))?;
+                }
+                None => write_themed_text(ElementType::Nodes(children), ctx, None, writer)?,
+            }
 
             if is_code_para {
                 writeln!(writer)?;
@@ -78,183 +421,174 @@ fn write_colored_text(
 
             Ok(())
         }
-        mdast::Node::Text(text) => write_themed_text(
-            ElementType::Text(&text.value),
-            theme,
-            None,
-            writer,
-            is_writer_tty,
-        ),
+        mdast::Node::Text(text) => {
+            write_themed_text(ElementType::Text(&text.value), ctx, None, writer)
+        }
         mdast::Node::Strong(strong) => write_themed_text(
             ElementType::Nodes(&strong.children),
-            theme,
+            ctx,
             Some(&theme.strong),
             writer,
-            is_writer_tty,
         ),
         mdast::Node::Emphasis(emphasis) => write_themed_text(
             ElementType::Nodes(&emphasis.children),
-            theme,
+            ctx,
             Some(&theme.emphasis),
             writer,
-            is_writer_tty,
         ),
-        mdast::Node::Blockquote(block_quote) => {
-            let mut write_intercept = Vec::new();
-            write_themed_text(
-                ElementType::Nodes(&block_quote.children),
-                theme,
-                None,
-                &mut write_intercept,
-                is_writer_tty,
-            )?;
-            let text = std::str::from_utf8(&write_intercept).unwrap();
-            let lines = text.lines();
-            for line in lines {
-                writeln!(writer, "│ {line}")?
-            }
-
-            Ok(())
-        }
+        mdast::Node::Blockquote(block_quote) => quote::write_blockquote(block_quote, ctx, writer),
         mdast::Node::Break(_) => {
             writeln!(writer)
         }
-        mdast::Node::Code(code) => {
-            writeln!(writer)?;
-            write_themed_text(
-                ElementType::Text(&code.value),
-                theme,
-                Some(&theme.code_block),
-                writer,
-                is_writer_tty,
-            )?;
-            writeln!(writer)
-        }
-        mdast::Node::InlineCode(code) => {
-            let code_text = format!(" {} ", &code.value)
-                .replace("{{", "")
-                .replace("}}", "");
-
-            write!(writer, "")?;
-
-            write_themed_text(
-                ElementType::Text(&code_text),
-                theme,
-                Some(&theme.code_block),
-                writer,
-                is_writer_tty,
-            )?;
-
-            write!(writer, "")
-        }
+        mdast::Node::Code(code) => code::write_code(code, ctx, writer),
+        mdast::Node::InlineCode(code) => write_themed_text(
+            ElementType::Text(&format!(" {} ", code.value)),
+            ctx,
+            Some(&theme.code_inline),
+            writer,
+        ),
+        mdast::Node::InlineMath(math) => math::write_inline_math(math, ctx, writer),
+        mdast::Node::Math(math) => math::write_math(math, ctx, writer),
         mdast::Node::Delete(delete) => write_themed_text(
             ElementType::Nodes(&delete.children),
-            theme,
+            ctx,
             Some(&theme.delete),
             writer,
-            is_writer_tty,
         ),
-        mdast::Node::Heading(heading) => {
-            // TODO: Build different styles for different depths
-            write!(writer, "\n ")?;
-            let header_theme = match heading.depth {
-                1 => &theme.header_1,
-                2 => {
-                    write!(writer, "##")?;
-                    &theme.header_x
-                }
-                3 => {
-                    write!(writer, "###")?;
-                    &theme.header_x
-                }
-                4 => {
-                    write!(writer, "####")?;
-                    &theme.header_x
-                }
-                _ => &theme.header_x,
-            };
-
-            write_themed_text(
-                ElementType::WhitespacePaddedNode(&heading.children),
-                theme,
-                Some(header_theme),
-                writer,
-                is_writer_tty,
-            )?;
-
-            write!(writer, " \n\n")
+        mdast::Node::Heading(heading) => heading::write_heading(heading, ctx, writer),
+        mdast::Node::Image(image) => image::write_image(image, ctx, writer),
+        mdast::Node::Link(link) => link::write_link(link, ctx, writer),
+        mdast::Node::LinkReference(reference) => link::write_link_reference(reference, ctx, writer),
+        mdast::Node::ImageReference(reference) => {
+            image::write_image_reference(reference, ctx, writer)
         }
-        mdast::Node::Image(_image) => {
-            // TODO: Fill in.
-            write!(writer, "")
+        mdast::Node::FootnoteReference(reference) => {
+            footnote::write_reference(reference, ctx, writer)
         }
-        mdast::Node::Link(link) => {
-            let link_text = &link.url;
-            if !is_writer_tty {
-                write_themed_text(
-                    ElementType::Text(link_text),
-                    theme,
-                    Some(&theme.link),
-                    writer,
-                    is_writer_tty,
-                )
-            } else {
-                write!(writer, "{T_ESC}]8;;{link_text}{T_ESC}\\")?;
-
-                write_themed_text(
-                    ElementType::Text(link_text),
-                    theme,
-                    Some(&theme.link),
-                    writer,
-                    is_writer_tty,
-                )?;
-                write!(writer, "{T_ESC}]8;;{T_ESC}\\")
-            }
+        mdast::Node::FootnoteDefinition(definition) => {
+            footnote::write_definition(definition, ctx, writer)
         }
-        mdast::Node::List(list) => write_themed_text(
-            ElementType::Nodes(&list.children),
-            theme,
-            None,
-            writer,
-            is_writer_tty,
-        ),
-        mdast::Node::ListItem(list_item) => {
-            write!(writer, "\n• ")?;
-            write_themed_text(
-                ElementType::Nodes(&list_item.children),
-                theme,
-                None,
-                writer,
-                is_writer_tty,
-            )?;
-            writeln!(writer)
+        mdast::Node::List(list) => list::write_list(list, ctx, writer),
+        mdast::Node::ThematicBreak(_) => write_rule(ctx, writer),
+        mdast::Node::Table(table) => table::write_table(table, ctx, writer),
+        mdast::Node::Html(html) => html::write_html_block(html, ctx, writer),
+        mdast::Node::Yaml(_) | mdast::Node::Toml(_) => {
+            frontmatter::write_frontmatter(node, ctx, writer)
         }
-        // mdast::Node::Table(_) => {
-        //     panic!("Tables are not supported")
-        // }
-        // mdast::Node::TableCell(_) => {
-        //     panic!("Tables are not supported")
-        // }
-        // mdast::Node::TableRow(_) => {
-        //     panic!("Tables are not supported")
-        // }
-        // mdast::Node::Html(_) => {
-        //     panic!("Html are not supported")
-        // }
         _ => {
             write!(writer, "")
         }
     }
 }
 
-fn write_raw_text(
-    children: &Vec<mdast::Node>,
-    theme: &Theme,
+/// Writes the children of a container such as the document, a block quote or a list item.
+///
+/// Paragraphs do not end with a line break, so the blocks that follow them are moved on
+/// to a new line. Blocks that do not start with a line break of their own are also
+/// separated from the paragraph by a blank line.
+fn write_blocks(
+    children: &[mdast::Node],
+    ctx: &Context,
+    writer: &mut impl std::io::Write,
+) -> Result<(), std::io::Error> {
+    write_blocks_after(children, None, ctx, writer)
+}
+
+/// Writes the blocks as if they followed the previous block, which has already been written.
+fn write_blocks_after<'a>(
+    children: &'a [mdast::Node],
+    mut previous: Option<&'a mdast::Node>,
+    ctx: &Context,
     writer: &mut impl std::io::Write,
-    is_writer_tty: &bool,
 ) -> Result<(), std::io::Error> {
     for child in children {
-        write_colored_text(child, theme, writer, is_writer_tty)?;
+        if !is_written_in_place(child, ctx.footnote_placement) {
+            if let mdast::Node::FootnoteDefinition(definition) = child {
+                ctx.footnotes.defer(definition);
+            }
+
+            continue;
+        }
+
+        // HTML blocks that are only comments or closing tags are left out, so that they
+        // do not leave blank lines behind.
+        if let mdast::Node::Html(html) = child {
+            if html::is_blank(html, ctx) {
+                continue;
+            }
+        }
+
+        // HTML blocks are written as text, so they are separated like paragraphs.
+        if let Some(mdast::Node::Paragraph(_) | mdast::Node::Html(_)) = previous {
+            match child {
+                mdast::Node::List(_) => {}
+                mdast::Node::Heading(_)
+                | mdast::Node::Code(_)
+                | mdast::Node::Math(_)
+                | mdast::Node::Table(_)
+                | mdast::Node::ThematicBreak(_) => writeln!(writer)?,
+                _ => write!(writer, "\n\n")?,
+            }
+        }
+
+        // Footnote definitions end with a line break, so only a blank line is left after
+        // them, unless the block starts with one of its own.
+        if let Some(mdast::Node::FootnoteDefinition(_)) = previous {
+            match child {
+                mdast::Node::List(_)
+                | mdast::Node::Heading(_)
+                | mdast::Node::Code(_)
+                | mdast::Node::Math(_)
+                | mdast::Node::Table(_)
+                | mdast::Node::ThematicBreak(_) => {}
+                _ => writeln!(writer)?,
+            }
+        }
+
+        write_colored_text(child, ctx, writer)?;
+        previous = Some(child);
+    }
+
+    Ok(())
+}
+
+/// Writes a rule across the wrap width for a thematic break.
+fn write_rule(ctx: &Context, writer: &mut impl std::io::Write) -> Result<(), std::io::Error> {
+    let theme = ctx.theme;
+    let rule_char = match ctx.unicode || theme.rule_char.is_ascii() {
+        true => theme.rule_char,
+        false => '-',
+    };
+
+    let rule = rule_char
+        .to_string()
+        .repeat(ctx.width.unwrap_or(RULE_WIDTH));
+
+    writeln!(writer)?;
+    theme
+        .rule
+        .write_with_depth(|w| write!(w, "{rule}"), writer, ctx.colors())?;
+    writeln!(writer)
+}
+
+/// Writes the inline nodes one after the other.
+///
+/// An inline HTML tag is written together with the nodes up to its closing tag, so that
+/// it can style them.
+fn write_raw_text(
+    children: &[mdast::Node],
+    ctx: &Context,
+    writer: &mut impl std::io::Write,
+) -> Result<(), std::io::Error> {
+    let mut index = 0;
+    while index < children.len() {
+        index = match &children[index] {
+            mdast::Node::Html(_) => html::write_inline_html(children, index, ctx, writer)?,
+            child => {
+                write_colored_text(child, ctx, writer)?;
+                index + 1
+            }
+        };
     }
 
     Ok(())
@@ -263,45 +597,49 @@ fn write_raw_text(
 #[derive(PartialEq)]
 enum ElementType<'a> {
     Text(&'a str),
-    Nodes(&'a Vec<mdast::Node>),
-    WhitespacePaddedNode(&'a Vec<mdast::Node>),
+    Nodes(&'a [mdast::Node]),
+    WhitespacePaddedNode(&'a [mdast::Node]),
 }
 
-fn write_themed_text(
+fn write_themed_text<W: std::io::Write>(
     input: ElementType,
-    theme: &Theme,
+    ctx: &Context,
     color: Option<&ElementTheme>,
-    writer: &mut impl std::io::Write,
-    is_writer_tty: &bool,
+    writer: &mut W,
 ) -> Result<(), std::io::Error> {
-    let color = color.unwrap_or(&ElementTheme {
-        fg: None,
-        bg: None,
-        style: TextStyle::Normal,
-    });
+    let write_input = |writer: &mut W, ctx: &Context| match input {
+        ElementType::Nodes(children) => write_raw_text(children, ctx, writer),
+        ElementType::Text(str) => {
+            write!(writer, "{str}")
+        }
+        ElementType::WhitespacePaddedNode(children) => {
+            write!(writer, " ")?;
+            write_raw_text(children, ctx, writer)?;
+            write!(writer, " ")
+        }
+    };
 
-    color.write(
-        |writer| match input {
-            ElementType::Nodes(children) => write_raw_text(children, theme, writer, is_writer_tty),
-            ElementType::Text(str) => {
-                write!(writer, "{str}")
-            }
-            ElementType::WhitespacePaddedNode(children) => {
-                write!(writer, " ")?;
-                write_raw_text(children, theme, writer, is_writer_tty)?;
-                write!(writer, " ")
-            }
-        },
-        writer,
-        is_writer_tty,
-    )
+    match color {
+        Some(color) if !color.is_plain() => {
+            // The children are written inside this element, so they restore its theme
+            // when they end rather than resetting it.
+            let inner_ctx = ctx.styled(color);
+            color.write_nested(
+                &ctx.style,
+                |writer| write_input(writer, &inner_ctx),
+                writer,
+                ctx.colors(),
+            )
+        }
+        _ => write_input(writer, ctx),
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
     use crate::themes::get_dark_theme;
-    use crate::{get_default_theme, Color};
+    use crate::{get_default_theme, Color, TextStyle};
     use colored::Colorize;
 
     macro_rules! string_match {
@@ -316,7 +654,7 @@ mod test {
                     let (value, expected_if_tty, _) = $value;
                     let theme = get_default_theme();
                     let mut result = Vec::new();
-                    let _ = write(value, &theme, &mut result, true);
+                    write(value, &theme, &mut result, true).unwrap();
                     let result = std::str::from_utf8(&result).unwrap();
 
                     println!("{:?}", result);
@@ -329,7 +667,7 @@ mod test {
                     let (value, _, expected_if_not_tty) = $value;
                     let theme = get_default_theme();
                     let mut result = Vec::new();
-                    let _ = write(value, &theme, &mut result, false);
+                    write(value, &theme, &mut result, false).unwrap();
                     let result = std::str::from_utf8(&result).unwrap();
 
                     println!("Result = {:?}\n Value = {:?}", result, value);
@@ -347,25 +685,50 @@ mod test {
         normal_plus_strong_text: ("This is **text**", format!("This is {}", "text".bold()), "This is text"),
         emphasis_text: ("*This text is italics*", "This text is italics".italic(), "This text is italics"),
         normal_plus_emphasis_text: ("This text is *italics*", format!("This text is {}", "italics".italic()), "This text is italics"),
-        blockquotes: ("> This is a blockquote", "│ This is a blockquote\n", "│ This is a blockquote\n"),
-        blockquotes_with_multiple_lines: (r#"
-> This is a blockquote
-> This is a blockquote"#, 
-    r#"│ This is a blockquote
-│ This is a blockquote
-"#,
-            r#"│ This is a blockquote
-│ This is a blockquote
-"#),
         line_breaks: ("This is a  \ntest", "This is a\ntest", "This is a\ntest"), // Note the two spaces before the newline. This generates a Break Node
         strikethrough: ("~Delete~", "Delete".strikethrough(), "Delete"), // Note the two spaces before the newline. This generates a Break Node
     }
 
+    #[test]
+    fn should_theme_blockquote_bars_if_tty() {
+        let theme = get_dark_theme();
+        let mut result = Vec::new();
+        write(
+            "> This is a blockquote\n> This is a blockquote",
+            &theme,
+            &mut result,
+            true,
+        )
+        .unwrap();
+
+        let bar = "│".custom_color(to_custom_color(theme.indents.fg.unwrap()));
+        let expected = format!("{bar} This is a blockquote\n{bar} This is a blockquote\n");
+
+        assert_eq!(std::str::from_utf8(&result).unwrap(), expected);
+    }
+
+    #[test]
+    fn should_write_blockquote_bars_if_not_tty() {
+        let theme = get_dark_theme();
+        let mut result = Vec::new();
+        write(
+            "> This is a blockquote\n> This is a blockquote",
+            &theme,
+            &mut result,
+            false,
+        )
+        .unwrap();
+
+        let expected = "│ This is a blockquote\n│ This is a blockquote\n";
+
+        assert_eq!(std::str::from_utf8(&result).unwrap(), expected);
+    }
+
     #[test]
     fn should_handle_headers_1_in_tty() {
         let theme = get_dark_theme();
         let mut result = Vec::new();
-        let _ = write("# This is a test", &theme, &mut result, true);
+        write("# This is a test", &theme, &mut result, true).unwrap();
 
         let result = std::str::from_utf8(&result).unwrap();
 
@@ -383,7 +746,7 @@ mod test {
     fn should_handle_headers_1_if_not_tty() {
         let theme = get_dark_theme();
         let mut result = Vec::new();
-        let _ = write("# This is a test", &theme, &mut result, false);
+        write("# This is a test", &theme, &mut result, false).unwrap();
 
         let result = std::str::from_utf8(&result).unwrap();
 
@@ -398,7 +761,7 @@ mod test {
     fn should_handle_headers_2_in_tty() {
         let theme = get_dark_theme();
         let mut result = Vec::new();
-        let _ = write("## This is a test", &theme, &mut result, true);
+        write("## This is a test", &theme, &mut result, true).unwrap();
 
         let result = std::str::from_utf8(&result).unwrap();
 
@@ -406,7 +769,7 @@ mod test {
 
         let expected = format!(
             "\n ##{} \n\n",
-            " This is a test ".custom_color(to_custom_color(theme.header_x.fg.unwrap()))
+            " This is a test ".custom_color(to_custom_color(theme.header_2.fg.unwrap()))
         );
 
         assert_eq!(result, expected);
@@ -416,7 +779,7 @@ mod test {
     fn should_handle_headers_2_if_not_tty() {
         let theme = get_dark_theme();
         let mut result = Vec::new();
-        let _ = write("## This is a test", &theme, &mut result, false);
+        write("## This is a test", &theme, &mut result, false).unwrap();
 
         let result = std::str::from_utf8(&result).unwrap();
 
@@ -431,7 +794,7 @@ mod test {
     fn should_handle_headers_3_if_tty() {
         let theme = get_dark_theme();
         let mut result = Vec::new();
-        let _ = write("### This is a test", &theme, &mut result, true);
+        write("### This is a test", &theme, &mut result, true).unwrap();
 
         let result = std::str::from_utf8(&result).unwrap();
 
@@ -439,7 +802,7 @@ mod test {
 
         let expected = format!(
             "\n ###{} \n\n",
-            " This is a test ".custom_color(to_custom_color(theme.header_x.fg.unwrap()))
+            " This is a test ".custom_color(to_custom_color(theme.header_2.fg.unwrap()))
         );
 
         assert_eq!(result, expected);
@@ -449,7 +812,7 @@ mod test {
     fn should_handle_headers_3_if_not_tty() {
         let theme = get_dark_theme();
         let mut result = Vec::new();
-        let _ = write("### This is a test", &theme, &mut result, false);
+        write("### This is a test", &theme, &mut result, false).unwrap();
 
         let result = std::str::from_utf8(&result).unwrap();
 
@@ -464,7 +827,7 @@ mod test {
     fn should_handle_headers_4_if_tty() {
         let theme = get_dark_theme();
         let mut result = Vec::new();
-        let _ = write("#### This is a test", &theme, &mut result, true);
+        write("#### This is a test", &theme, &mut result, true).unwrap();
 
         let result = std::str::from_utf8(&result).unwrap();
 
@@ -472,7 +835,7 @@ mod test {
 
         let expected = format!(
             "\n ####{} \n\n",
-            " This is a test ".custom_color(to_custom_color(theme.header_x.fg.unwrap()))
+            " This is a test ".custom_color(to_custom_color(theme.header_2.fg.unwrap()))
         );
 
         assert_eq!(result, expected);
@@ -482,7 +845,7 @@ mod test {
     fn should_handle_headers_4_if_not_tty() {
         let theme = get_dark_theme();
         let mut result = Vec::new();
-        let _ = write("#### This is a test", &theme, &mut result, false);
+        write("#### This is a test", &theme, &mut result, false).unwrap();
 
         let result = std::str::from_utf8(&result).unwrap();
 
@@ -497,7 +860,7 @@ mod test {
     fn should_pretty_print_code_if_tty() {
         let theme = get_dark_theme();
         let mut result = Vec::new();
-        let _ = write("`This is a test`", &theme, &mut result, true);
+        write("`This is a test`", &theme, &mut result, true).unwrap();
 
         let result = std::str::from_utf8(&result).unwrap();
 
@@ -506,8 +869,8 @@ mod test {
         let expected = format!(
             "\n{}\n",
             " This is a test "
-                .custom_color(to_custom_color(theme.code_block.fg.unwrap()))
-                .on_custom_color(to_custom_color(theme.code_block.bg.unwrap()))
+                .custom_color(to_custom_color(theme.code_inline.fg.unwrap()))
+                .on_custom_color(to_custom_color(theme.code_inline.bg.unwrap()))
         );
 
         assert_eq!(result, expected);
@@ -517,7 +880,7 @@ mod test {
     fn should_pretty_print_code_if_not_tty() {
         let theme = get_dark_theme();
         let mut result = Vec::new();
-        let _ = write("`This is a test`", &theme, &mut result, false);
+        write("`This is a test`", &theme, &mut result, false).unwrap();
 
         let result = std::str::from_utf8(&result).unwrap();
 
@@ -528,11 +891,20 @@ mod test {
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn should_write_inline_code_verbatim() {
+        let theme = get_dark_theme();
+        let mut result = Vec::new();
+        write("Use `{{name}}` here", &theme, &mut result, false).unwrap();
+
+        assert_eq!(std::str::from_utf8(&result).unwrap(), "Use  {{name}}  here");
+    }
+
     #[test]
     fn should_add_hyperlink_to_links_if_tty() {
         let theme = get_dark_theme();
         let mut result = Vec::new();
-        let _ = write("<http://google.com>", &theme, &mut result, true);
+        write("<http://google.com>", &theme, &mut result, true).unwrap();
         let result = std::str::from_utf8(&result).unwrap();
 
         let link = "http://google.com";
@@ -552,7 +924,7 @@ mod test {
     fn should_not_hyperlink_to_links_if_not_tty() {
         let theme = get_dark_theme();
         let mut result = Vec::new();
-        let _ = write("<http://google.com>", &theme, &mut result, false);
+        write("<http://google.com>", &theme, &mut result, false).unwrap();
         let result = std::str::from_utf8(&result).unwrap();
 
         let expected = "http://google.com";
@@ -567,7 +939,7 @@ mod test {
         let input = r#"- List Item 1
 - List Item 2"#;
 
-        let _ = write(input, &theme, &mut result, true);
+        write(input, &theme, &mut result, true).unwrap();
         let result = std::str::from_utf8(&result).unwrap();
         println!("{result:?}");
 
@@ -586,7 +958,7 @@ mod test {
         let input = r#"- List Item 1
 - List Item 2"#;
 
-        let _ = write(input, &theme, &mut result, false);
+        write(input, &theme, &mut result, false).unwrap();
         let result = std::str::from_utf8(&result).unwrap();
         println!("{result:?}");
 
@@ -598,11 +970,181 @@ mod test {
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn should_indent_nested_lists() {
+        let theme = get_dark_theme();
+        let mut result = Vec::new();
+        let input = r#"- Item
+  - Nested
+    - Deep"#;
+
+        write(input, &theme, &mut result, false).unwrap();
+        let result = std::str::from_utf8(&result).unwrap();
+        println!("{result:?}");
+
+        let expected = "\n• Item\n  ◦ Nested\n    ▪ Deep\n";
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn should_use_bullets_and_indent_from_theme() {
+        let theme = Theme {
+            list_bullets: vec!['-', '*'],
+            list_indent: 4,
+            ..get_dark_theme()
+        };
+        let mut result = Vec::new();
+        let input = r#"- Item
+  - Nested
+    - Deep"#;
+
+        write(input, &theme, &mut result, false).unwrap();
+        let result = std::str::from_utf8(&result).unwrap();
+        println!("{result:?}");
+
+        let expected = "\n-   Item\n    *   Nested\n        -   Deep\n";
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn should_align_continuation_paragraphs_with_item_text() {
+        let theme = get_dark_theme();
+        let mut result = Vec::new();
+        let input = r#"- First line
+  second line
+
+  Another paragraph"#;
+
+        write(input, &theme, &mut result, false).unwrap();
+        let result = std::str::from_utf8(&result).unwrap();
+        println!("{result:?}");
+
+        let expected = "\n• First line\n  second line\n\n  Another paragraph\n";
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn should_separate_paragraphs() {
+        let theme = get_dark_theme();
+        let mut result = Vec::new();
+
+        write("First\n\nSecond", &theme, &mut result, false).unwrap();
+        let result = std::str::from_utf8(&result).unwrap();
+
+        assert_eq!(result, "First\n\nSecond");
+    }
+
+    #[test]
+    fn should_restore_enclosing_style_after_nested_style() {
+        let theme = get_dark_theme();
+        let mut result = Vec::new();
+
+        write("**bold *both* bold**", &theme, &mut result, true).unwrap();
+        let result = std::str::from_utf8(&result).unwrap();
+
+        assert_eq!(
+            result,
+            "\u{1b}[1mbold \u{1b}[1;3mboth\u{1b}[0m\u{1b}[1m bold\u{1b}[0m"
+        );
+    }
+
+    #[test]
+    fn should_combine_strong_and_emphasis() {
+        let theme = get_dark_theme();
+        let mut result = Vec::new();
+
+        write("***text***", &theme, &mut result, true).unwrap();
+        let result = std::str::from_utf8(&result).unwrap();
+
+        assert!(result.contains("\u{1b}[1;3mtext\u{1b}[0m"));
+    }
+
+    #[test]
+    fn should_combine_colors_and_styles_of_nested_elements() {
+        let theme = Theme {
+            strong: ElementTheme::new(Some("#F00"), None, TextStyle::Bold | TextStyle::Overlined),
+            ..get_dark_theme()
+        };
+        let mut result = Vec::new();
+
+        write("**a ~b~**", &theme, &mut result, true).unwrap();
+        let result = std::str::from_utf8(&result).unwrap();
+
+        assert_eq!(
+            result,
+            "\u{1b}[1;53;38;2;255;0;0ma \u{1b}[1;9;53;38;2;255;0;0mb\u{1b}[0m\u{1b}[1;53;38;2;255;0;0m\u{1b}[0m"
+        );
+    }
+
+    fn write_wrapped(input: &str, width: usize, is_writer_tty: bool) -> String {
+        let theme = get_dark_theme();
+        let options = RenderOptions {
+            width: Some(width),
+            ..test_options()
+        };
+
+        render_with_options(input, &theme, &options, is_writer_tty)
+    }
+
+    #[test]
+    fn should_write_rule_across_width() {
+        let result = write_wrapped("Above\n\n---\n\nBelow", 6, false);
+
+        assert_eq!(result, "Above\n\n──────\nBelow");
+    }
+
+    #[test]
+    fn should_theme_rule_if_tty() {
+        let result = write_wrapped("***", 3, true);
+
+        assert_eq!(result, "\n\u{1b}[38;2;85;85;85m───\u{1b}[0m\n");
+    }
+
+    #[test]
+    fn should_draw_rule_with_ascii_if_not_unicode() {
+        let options = RenderOptions {
+            width: Some(4),
+            unicode: false,
+            ..test_options()
+        };
+        let result = render_with_options("---", &get_dark_theme(), &options, false);
+
+        assert_eq!(result, "\n----\n");
+    }
+
+    #[test]
+    fn should_wrap_paragraphs_to_width() {
+        let result = write_wrapped("The quick brown fox jumps over the lazy dog", 16, false);
+
+        assert_eq!(result, "The quick brown\nfox jumps over\nthe lazy dog");
+    }
+
+    #[test]
+    fn should_keep_blockquote_prefix_on_wrapped_lines() {
+        let result = write_wrapped("> The quick brown fox jumps", 12, false);
+
+        assert_eq!(result, "│ The quick\n│ brown fox\n│ jumps\n");
+    }
+
+    #[test]
+    fn should_indent_wrapped_list_items() {
+        let result = write_wrapped("- The quick brown fox jumps", 12, false);
+
+        assert_eq!(result, "\n• The quick\n  brown fox\n  jumps\n");
+    }
+
+    #[test]
+    fn should_not_style_prefix_of_wrapped_lines() {
+        let result = write_wrapped("> **The quick brown**", 12, true);
+        let theme = get_dark_theme();
+        let bar = "│".custom_color(to_custom_color(theme.indents.fg.unwrap()));
+        let expected = format!("{bar} {}\n{bar} {}\n", "The quick".bold(), "brown".bold());
+
+        assert_eq!(result, expected);
+    }
+
     fn to_custom_color(color: Color) -> colored::CustomColor {
-        colored::CustomColor {
-            r: color.r,
-            g: color.g,
-            b: color.b,
-        }
+        let (r, g, b) = color.to_rgb();
+        colored::CustomColor::new(r, g, b)
     }
 }
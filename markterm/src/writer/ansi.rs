@@ -0,0 +1,131 @@
+use unicode_width::UnicodeWidthChar;
+
+/// A part of a string that is either printable text or a terminal escape sequence.
+#[derive(Debug, PartialEq)]
+pub enum Segment<'a> {
+    /// Text that is printed.
+    Text(&'a str),
+
+    /// An escape sequence that changes the colors, styles or hyperlink.
+    Escape(&'a str),
+}
+
+/// Splits the text into the escape sequences and the text between them.
+pub fn segments(text: &str) -> Vec<Segment<'_>> {
+    let mut segments = Vec::new();
+    let mut text_start = 0;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        if c != '\u{1b}' {
+            continue;
+        }
+
+        let mut end = start + c.len_utf8();
+        match chars.next() {
+            // CSI sequences such as `ESC[1;38;2;0;0;0m` end with a byte in the range @ to ~.
+            Some((i, '[')) => {
+                end = i + 1;
+                for (i, c) in chars.by_ref() {
+                    end = i + c.len_utf8();
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            // OSC sequences such as hyperlinks, and the DCS and APC sequences used to draw
+            // images, end with either BEL or `ESC\`.
+            Some((i, ']' | 'P' | '_' | '^' | 'X')) => {
+                end = i + 1;
+                while let Some((i, c)) = chars.next() {
+                    end = i + c.len_utf8();
+                    if c == '\u{7}' {
+                        break;
+                    }
+
+                    if c == '\u{1b}' && chars.peek().map(|(_, c)| *c) == Some('\\') {
+                        chars.next();
+                        end += 1;
+                        break;
+                    }
+                }
+            }
+            Some((i, c)) => end = i + c.len_utf8(),
+            None => {}
+        }
+
+        if start > text_start {
+            segments.push(Segment::Text(&text[text_start..start]));
+        }
+
+        segments.push(Segment::Escape(&text[start..end]));
+        text_start = end;
+    }
+
+    if text_start < text.len() {
+        segments.push(Segment::Text(&text[text_start..]));
+    }
+
+    segments
+}
+
+/// Gets the number of terminal columns the text occupies once printed.
+///
+/// Escape sequences (colors, styles and hyperlinks) do not take up any space
+/// and are ignored. Wide characters such as CJK ideographs count as 2 columns.
+pub fn visible_width(text: &str) -> usize {
+    segments(text)
+        .iter()
+        .map(|segment| match segment {
+            Segment::Text(text) => text.chars().map(char_width).sum(),
+            Segment::Escape(_) => 0,
+        })
+        .sum()
+}
+
+/// Gets the number of terminal columns the character occupies.
+pub fn char_width(c: char) -> usize {
+    c.width().unwrap_or(0)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    macro_rules! width_test {
+        ($($name:ident: $value:expr,)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    let (input, expected) = $value;
+                    assert_eq!(visible_width(input), expected);
+                }
+            )*
+        }
+    }
+
+    width_test! {
+        should_count_plain_text: ("Hello", 5),
+        should_ignore_colors: ("\u{1b}[1;38;2;0;0;0mHello\u{1b}[0m", 5),
+        should_ignore_hyperlinks: ("\u{1b}]8;;http://a.com\u{1b}\\Hello\u{1b}]8;;\u{1b}\\", 5),
+        should_ignore_images: ("\u{1b}_Ga=T,f=100;iVBO\u{1b}\\\u{1b}Pq#0!6~\u{1b}\\Hello", 5),
+        should_count_wide_characters_twice: ("日本", 4),
+        should_count_box_characters_once: ("│ a │", 5),
+    }
+
+    #[test]
+    fn should_split_escapes_from_text() {
+        let result = segments("a\u{1b}[1mb\u{1b}]8;;x\u{1b}\\c");
+
+        assert_eq!(
+            result,
+            vec![
+                Segment::Text("a"),
+                Segment::Escape("\u{1b}[1m"),
+                Segment::Text("b"),
+                Segment::Escape("\u{1b}]8;;x\u{1b}\\"),
+                Segment::Text("c"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,226 @@
+use super::{ansi::visible_width, Context};
+use markdown::mdast;
+use std::io::Write;
+
+/// Writes a fenced or indented code block as a panel.
+///
+/// When the output is colored, the background of the code block is filled to the wrap
+/// width, or to the longest line when text is not wrapped. The panel has a margin on
+/// both sides, and can have the language of the code block above the code and the
+/// numbers of the lines in front of it.
+///
+/// When the output is not colored, the code is written as it is, so that it can be
+/// copied. Only the line numbers are kept.
+pub fn write_code<W: Write>(
+    code: &mdast::Code,
+    ctx: &Context,
+    writer: &mut W,
+) -> Result<(), std::io::Error> {
+    let theme = ctx.theme;
+    let raw_lines: Vec<&str> = code.value.split('\n').collect();
+    let gutter_width = match theme.code_block_show_line_numbers {
+        true => raw_lines.len().to_string().len() + 1,
+        false => 0,
+    };
+
+    writeln!(writer)?;
+
+    let Some(colors) = ctx.colors() else {
+        for (index, line) in raw_lines.iter().enumerate() {
+            if gutter_width > 0 {
+                write!(writer, "{:>1$} ", index + 1, gutter_width - 1)?;
+            }
+
+            writeln!(writer, "{line}")?;
+        }
+
+        return Ok(());
+    };
+
+    let lines = match highlighted_lines(code, ctx)? {
+        Some(lines) => lines,
+        None => raw_lines
+            .iter()
+            .map(|line| {
+                let mut result = Vec::new();
+                if !line.is_empty() {
+                    theme.code_block.write_with_depth(
+                        |w| write!(w, "{line}"),
+                        &mut result,
+                        Some(colors),
+                    )?;
+                }
+
+                Ok(result)
+            })
+            .collect::<Result<_, std::io::Error>>()?,
+    };
+
+    let margin = theme.code_block_margin;
+    let label = match (theme.code_block_show_label, code.lang.as_deref()) {
+        (true, Some(lang)) if !lang.is_empty() => Some(lang),
+        _ => None,
+    };
+
+    let widths: Vec<usize> = raw_lines.iter().map(|line| visible_width(line)).collect();
+    let content_width = widths
+        .iter()
+        .map(|width| width + gutter_width)
+        .chain(label.map(visible_width))
+        .max()
+        .unwrap_or(0);
+    let panel_width = ctx
+        .width
+        .unwrap_or(0)
+        .max(content_width + 2 * margin)
+        .saturating_sub(2 * margin);
+
+    let fill = |writer: &mut W, width: usize| {
+        theme.code_block.write_with_depth(
+            |w: &mut W| write!(w, "{}", " ".repeat(width)),
+            writer,
+            Some(colors),
+        )
+    };
+
+    if let Some(label) = label {
+        let label_theme = theme.code_block.combine(&theme.code_block_label);
+
+        fill(writer, margin)?;
+        label_theme.write_with_depth(|w| write!(w, "{label}"), writer, Some(colors))?;
+        fill(writer, panel_width - visible_width(label) + margin)?;
+        writeln!(writer)?;
+    }
+
+    let gutter_theme = theme.code_block.combine(&theme.code_block_gutter);
+    for (index, (line, width)) in lines.iter().zip(widths).enumerate() {
+        fill(writer, margin)?;
+        if gutter_width > 0 {
+            gutter_theme.write_with_depth(
+                |w| write!(w, "{:>1$} ", index + 1, gutter_width - 1),
+                writer,
+                Some(colors),
+            )?;
+        }
+
+        writer.write_all(line)?;
+        fill(
+            writer,
+            panel_width.saturating_sub(width + gutter_width) + margin,
+        )?;
+        writeln!(writer)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "syntax-highlighting")]
+fn highlighted_lines(
+    code: &mdast::Code,
+    ctx: &Context,
+) -> Result<Option<Vec<Vec<u8>>>, std::io::Error> {
+    super::highlight::highlight_lines(code, ctx)
+}
+
+#[cfg(not(feature = "syntax-highlighting"))]
+fn highlighted_lines(
+    _code: &mdast::Code,
+    _ctx: &Context,
+) -> Result<Option<Vec<Vec<u8>>>, std::io::Error> {
+    Ok(None)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::renderer::RenderOptions;
+    use crate::themes::get_dark_theme;
+    use crate::writer::{render_with_options, test_options, write};
+    use crate::{ColorDepth, ElementTheme, TextStyle, Theme};
+
+    fn render(input: &str, theme: &Theme, width: Option<usize>, is_writer_tty: bool) -> String {
+        let options = RenderOptions {
+            width,
+            color_depth: ColorDepth::Ansi256,
+            ..test_options()
+        };
+
+        render_with_options(input, theme, &options, is_writer_tty)
+    }
+
+    fn plain_theme() -> Theme {
+        Theme {
+            code_block: ElementTheme::new(None, Some("256:236"), TextStyle::Normal),
+            code_block_label: ElementTheme::new(None, None, TextStyle::Italics),
+            code_block_gutter: ElementTheme::new(Some("256:240"), None, TextStyle::Normal),
+            ..get_dark_theme()
+        }
+    }
+
+    #[test]
+    fn should_fill_background_to_width() {
+        let result = render("```\nab\n```", &plain_theme(), Some(6), true);
+
+        assert_eq!(
+            result,
+            "\n\u{1b}[48;5;236m \u{1b}[0m\u{1b}[48;5;236mab\u{1b}[0m\u{1b}[48;5;236m   \u{1b}[0m\n"
+        );
+    }
+
+    #[test]
+    fn should_fill_background_to_longest_line_if_not_wrapped() {
+        let result = render("```\nab\nabcd\n```", &plain_theme(), None, true);
+        let lines: Vec<&str> = result.lines().collect();
+
+        assert!(lines[1].ends_with("ab\u{1b}[0m\u{1b}[48;5;236m   \u{1b}[0m"));
+        assert!(lines[2].ends_with("abcd\u{1b}[0m\u{1b}[48;5;236m \u{1b}[0m"));
+    }
+
+    #[test]
+    fn should_write_language_label() {
+        let result = render("```text\nab\n```", &plain_theme(), Some(8), true);
+        let label = result.lines().nth(1).unwrap();
+
+        assert_eq!(
+            label,
+            "\u{1b}[48;5;236m \u{1b}[0m\u{1b}[3;48;5;236mtext\u{1b}[0m\u{1b}[48;5;236m   \u{1b}[0m"
+        );
+    }
+
+    #[test]
+    fn should_not_write_language_label_if_disabled() {
+        let theme = Theme {
+            code_block_show_label: false,
+            ..plain_theme()
+        };
+        let result = render("```text\nab\n```", &theme, Some(8), true);
+
+        assert!(!result.contains("text"));
+    }
+
+    #[test]
+    fn should_write_line_numbers() {
+        let theme = Theme {
+            code_block_show_line_numbers: true,
+            code_block_show_label: false,
+            ..plain_theme()
+        };
+        let input = "```\n1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n```";
+
+        let result = render(input, &theme, None, true);
+        assert!(result.contains("\u{1b}[48;5;236;38;5;240m 1 \u{1b}[0m\u{1b}[48;5;236m1\u{1b}"));
+        assert!(result.contains("\u{1b}[48;5;236;38;5;240m10 \u{1b}[0m\u{1b}[48;5;236m10\u{1b}"));
+
+        let mut plain = Vec::new();
+        write(input, &theme, &mut plain, false).unwrap();
+        let plain = String::from_utf8(plain).unwrap();
+        assert!(plain.starts_with("\n 1 1\n 2 2\n"));
+        assert!(plain.ends_with("10 10\n"));
+    }
+
+    #[test]
+    fn should_write_code_as_it_is_if_not_tty() {
+        let result = render("```rust\n  let x;\n\n```", &plain_theme(), Some(20), false);
+
+        assert_eq!(result, "\n  let x;\n\n");
+    }
+}
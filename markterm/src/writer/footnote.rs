@@ -0,0 +1,209 @@
+use super::quote::LinePrefixer;
+use super::{write_blocks, write_themed_text, Context, ElementType};
+use crate::Theme;
+use markdown::mdast;
+use std::cell::RefCell;
+use std::io::Write;
+
+/// The footnotes of a document, numbered in the order they are first referenced.
+///
+/// The definitions that are written at the end of the document are kept here until the
+/// rest of the document has been written.
+#[derive(Debug, Default)]
+pub struct Footnotes {
+    identifiers: RefCell<Vec<String>>,
+    definitions: RefCell<Vec<mdast::FootnoteDefinition>>,
+}
+
+impl Footnotes {
+    /// Gets the number of the footnote, numbering it if it has not been seen before.
+    fn number(&self, identifier: &str) -> usize {
+        let mut identifiers = self.identifiers.borrow_mut();
+        match identifiers.iter().position(|known| known == identifier) {
+            Some(index) => index + 1,
+            None => {
+                identifiers.push(identifier.to_string());
+                identifiers.len()
+            }
+        }
+    }
+
+    /// Keeps the definition to be written at the end of the document.
+    pub(super) fn defer(&self, definition: &mdast::FootnoteDefinition) {
+        self.definitions.borrow_mut().push(definition.clone());
+    }
+}
+
+/// Writes the marker of a footnote.
+///
+/// The marker is not a hyperlink to the definition, for the reasons given on
+/// [`crate::FootnotePlacement`].
+pub fn write_reference(
+    reference: &mdast::FootnoteReference,
+    ctx: &Context,
+    writer: &mut impl Write,
+) -> Result<(), std::io::Error> {
+    let number = ctx.footnotes.number(&reference.identifier);
+    write_marker(number, ctx, writer)
+}
+
+/// Writes a footnote definition, with the marker in front of its first line and the
+/// rest of its lines aligned to the text after the marker.
+pub fn write_definition(
+    definition: &mdast::FootnoteDefinition,
+    ctx: &Context,
+    writer: &mut impl Write,
+) -> Result<(), std::io::Error> {
+    let number = ctx.footnotes.number(&definition.identifier);
+    let marker = marker(number, ctx.theme);
+    let indent = marker.chars().count() + 1;
+
+    write_marker(number, ctx, writer)?;
+    write!(writer, " ")?;
+
+    let prefix = " ".repeat(indent);
+    let mut writer = LinePrefixer {
+        inner: writer,
+        prefix: prefix.as_bytes(),
+        is_line_start: false,
+    };
+
+    write_blocks(&definition.children, &ctx.indented(indent), &mut writer)?;
+    if !writer.is_line_start {
+        writeln!(writer.inner)?;
+    }
+
+    Ok(())
+}
+
+/// Writes the footnotes that were kept for the end of the document under a title.
+/// Returns whether anything was written.
+///
+/// `separator` is written before the title, to separate the footnotes from the document.
+pub fn write_footnotes(
+    separator: &str,
+    ctx: &Context,
+    writer: &mut impl Write,
+) -> Result<bool, std::io::Error> {
+    let definitions = ctx.footnotes.definitions.take();
+    if definitions.is_empty() {
+        return Ok(false);
+    }
+
+    // Footnotes that are never referenced are numbered after the rest.
+    let mut definitions: Vec<_> = definitions
+        .into_iter()
+        .map(|definition| (ctx.footnotes.number(&definition.identifier), definition))
+        .collect();
+    definitions.sort_by_key(|(number, _)| *number);
+
+    write!(writer, "{separator}")?;
+    write_themed_text(
+        ElementType::Text("Footnotes"),
+        ctx,
+        Some(&ctx.theme.footnote),
+        writer,
+    )?;
+    writeln!(writer)?;
+
+    for (_, definition) in &definitions {
+        write_definition(definition, ctx, writer)?;
+    }
+
+    Ok(true)
+}
+
+/// Writes the marker of the footnote with the footnote theme.
+fn write_marker(
+    number: usize,
+    ctx: &Context,
+    writer: &mut impl Write,
+) -> Result<(), std::io::Error> {
+    write_themed_text(
+        ElementType::Text(&marker(number, ctx.theme)),
+        ctx,
+        Some(&ctx.theme.footnote),
+        writer,
+    )
+}
+
+/// Gets the marker of the footnote, which is either `[1]` or `¹`.
+fn marker(number: usize, theme: &Theme) -> String {
+    if !theme.footnote_superscripts {
+        return format!("[{number}]");
+    }
+
+    const SUPERSCRIPTS: [char; 10] = ['⁰', '¹', '²', '³', '⁴', '⁵', '⁶', '⁷', '⁸', '⁹'];
+    number
+        .to_string()
+        .bytes()
+        .map(|digit| SUPERSCRIPTS[(digit - b'0') as usize])
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::renderer::RenderOptions;
+    use crate::themes::get_dark_theme;
+    use crate::writer::{render_with_options, test_options};
+    use crate::{ElementTheme, FootnotePlacement, Theme};
+
+    fn render(input: &str, theme: &Theme, footnotes: FootnotePlacement, tty: bool) -> String {
+        let options = RenderOptions {
+            footnotes,
+            ..test_options()
+        };
+
+        render_with_options(input, theme, &options, tty)
+    }
+
+    const INPUT: &str = "A[^b] and[^a].\n\n[^a]: First\n    second\n\n[^b]: Note\n\nAfter\n";
+
+    #[test]
+    fn should_write_footnotes_at_end_in_order_of_reference() {
+        let result = render(INPUT, &get_dark_theme(), FootnotePlacement::End, false);
+
+        assert_eq!(
+            result,
+            "A[1] and[2].\n\nAfter\n\nFootnotes\n[1] Note\n[2] First\n    second\n"
+        );
+    }
+
+    #[test]
+    fn should_write_footnotes_where_defined() {
+        let result = render(
+            INPUT,
+            &get_dark_theme(),
+            FootnotePlacement::Definition,
+            false,
+        );
+
+        assert_eq!(
+            result,
+            "A[1] and[2].\n\n[2] First\n    second\n\n[1] Note\n\nAfter"
+        );
+    }
+
+    #[test]
+    fn should_not_link_references_and_definitions_if_tty() {
+        let theme = Theme {
+            footnote: ElementTheme::default(),
+            ..get_dark_theme()
+        };
+        let result = render("A[^a]\n\n[^a]: Note", &theme, FootnotePlacement::End, true);
+
+        assert_eq!(result, "A[1]\n\nFootnotes\n[1] Note\n");
+    }
+
+    #[test]
+    fn should_write_superscript_markers() {
+        let theme = Theme {
+            footnote_superscripts: true,
+            ..get_dark_theme()
+        };
+        let input = "A[^a]\n\n[^a]: Note";
+        let result = render(input, &theme, FootnotePlacement::Definition, false);
+
+        assert_eq!(result, "A¹\n\n¹ Note\n");
+    }
+}
@@ -0,0 +1,165 @@
+use super::{ansi::visible_width, Context};
+use crate::{Frontmatter, FrontmatterDisplay};
+use markdown::mdast;
+use std::io::Write;
+
+/// Writes the frontmatter of the document as a table of its fields or as a banner with
+/// its title, or leaves it out.
+pub fn write_frontmatter(
+    node: &mdast::Node,
+    ctx: &Context,
+    writer: &mut impl Write,
+) -> Result<(), std::io::Error> {
+    let Some(frontmatter) = Frontmatter::from_node(node) else {
+        return Ok(());
+    };
+
+    match ctx.frontmatter {
+        FrontmatterDisplay::Hide => Ok(()),
+        FrontmatterDisplay::Table => write_table(&frontmatter, ctx, writer),
+        FrontmatterDisplay::Banner => write_banner(&frontmatter, ctx, writer),
+    }
+}
+
+/// Writes every field on its own line, with the values aligned after the longest key.
+/// The lines of values that have more than one line are aligned with the first line.
+fn write_table(
+    frontmatter: &Frontmatter,
+    ctx: &Context,
+    writer: &mut impl Write,
+) -> Result<(), std::io::Error> {
+    let fields = &frontmatter.fields;
+    if fields.is_empty() {
+        return Ok(());
+    }
+
+    let key_width = (fields.iter())
+        .map(|(key, _)| visible_width(key))
+        .max()
+        .unwrap_or(0);
+
+    let indent = " ".repeat(key_width + 2);
+    for (key, value) in fields {
+        let padding = " ".repeat(key_width - visible_width(key) + 2);
+        let value = value.replace('\n', &format!("\n{indent}"));
+        for (index, line) in format!("{key}{padding}{value}").lines().enumerate() {
+            if index > 0 {
+                writeln!(writer)?;
+            }
+
+            let line = line.trim_end();
+            if !line.is_empty() {
+                ctx.theme.frontmatter.write_with_depth(
+                    |w| write!(w, "{line}"),
+                    writer,
+                    ctx.colors(),
+                )?;
+            }
+        }
+
+        writeln!(writer)?;
+    }
+
+    writeln!(writer)
+}
+
+/// Writes the title centered in a banner across the wrap width. Nothing is written
+/// when the frontmatter has no title.
+fn write_banner(
+    frontmatter: &Frontmatter,
+    ctx: &Context,
+    writer: &mut impl Write,
+) -> Result<(), std::io::Error> {
+    let Some(title) = frontmatter.title() else {
+        return Ok(());
+    };
+
+    let title_width = visible_width(title);
+    let padding = ctx.width.unwrap_or(0).max(title_width + 2) - title_width;
+    let left = " ".repeat(padding / 2);
+
+    // The right side is only filled when the background of the banner is drawn.
+    let right = match ctx.colors() {
+        Some(_) => " ".repeat(padding - padding / 2),
+        None => String::new(),
+    };
+
+    writeln!(writer)?;
+    ctx.theme.frontmatter_banner.write_with_depth(
+        |w| write!(w, "{left}{title}{right}"),
+        writer,
+        ctx.colors(),
+    )?;
+    write!(writer, "\n\n")
+}
+
+#[cfg(test)]
+mod test {
+    use crate::renderer::RenderOptions;
+    use crate::themes::get_dark_theme;
+    use crate::writer::{render_with_options, test_options};
+    use crate::{ElementTheme, FrontmatterDisplay, TextStyle, Theme};
+
+    const INPUT: &str = "---\ntitle: Hello\nauthor: Me\n---\nText";
+
+    fn render(
+        frontmatter: FrontmatterDisplay,
+        width: Option<usize>,
+        is_writer_tty: bool,
+    ) -> String {
+        let theme = Theme {
+            frontmatter_banner: ElementTheme::new(None, Some("#00F"), TextStyle::Normal),
+            ..get_dark_theme()
+        };
+        let options = RenderOptions {
+            width,
+            frontmatter,
+            ..test_options()
+        };
+
+        render_with_options(INPUT, &theme, &options, is_writer_tty)
+    }
+
+    #[test]
+    fn should_hide_frontmatter() {
+        let result = render(FrontmatterDisplay::Hide, None, false);
+
+        assert_eq!(result, "Text");
+    }
+
+    #[test]
+    fn should_write_fields_as_table() {
+        let result = render(FrontmatterDisplay::Table, None, true);
+
+        assert_eq!(
+            result,
+            "\u{1b}[2mtitle   Hello\u{1b}[0m\n\u{1b}[2mauthor  Me\u{1b}[0m\n\nText"
+        );
+    }
+
+    #[test]
+    fn should_align_lines_of_values_in_table() {
+        let options = RenderOptions {
+            frontmatter: FrontmatterDisplay::Table,
+            ..test_options()
+        };
+        let input = "---\nid: 1\nabout: |\n  One\n\n  Two\n---\nText";
+        let result = render_with_options(input, &get_dark_theme(), &options, false);
+
+        assert_eq!(result, "id     1\nabout  One\n\n       Two\n\nText");
+    }
+
+    #[test]
+    fn should_write_title_as_banner() {
+        let result = render(FrontmatterDisplay::Banner, Some(9), true);
+
+        assert_eq!(result, "\n\u{1b}[48;2;0;0;255m  Hello  \u{1b}[0m\n\nText");
+    }
+
+    #[test]
+    fn should_not_fill_banner_if_not_tty() {
+        let result = render(FrontmatterDisplay::Banner, Some(9), false);
+
+        assert_eq!(result, "\n  Hello\n\nText");
+    }
+}
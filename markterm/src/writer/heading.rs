@@ -0,0 +1,163 @@
+use super::{ansi::visible_width, write_themed_text, Context, ElementType};
+use crate::{ElementTheme, HeadingFormat, HeadingPrefix, Theme};
+use markdown::mdast;
+use std::cell::Cell;
+
+/// Counts the headings of every level that have been written, so that headings can be
+/// numbered within the headings that enclose them.
+#[derive(Debug, Default)]
+pub struct HeadingNumbers(Cell<[u32; 6]>);
+
+impl HeadingNumbers {
+    /// Counts a heading of the level, and returns the number of the last heading of every
+    /// level. The numbers of the levels below it start again.
+    fn next(&self, level: usize) -> [u32; 6] {
+        let mut numbers = self.0.get();
+        numbers[level - 1] += 1;
+        numbers[level..].fill(0);
+
+        self.0.set(numbers);
+        numbers
+    }
+}
+
+/// Writes a heading with the theme and the format of its level, followed by a rule
+/// when the format has one.
+pub fn write_heading(
+    heading: &mdast::Heading,
+    ctx: &Context,
+    writer: &mut impl std::io::Write,
+) -> Result<(), std::io::Error> {
+    let level = (heading.depth as usize).clamp(1, 6);
+    let theme = ctx.theme;
+    let numbers = ctx.headings.next(level);
+
+    let format = header_format(theme, level);
+    let prefix = match format.prefix {
+        HeadingPrefix::None => String::new(),
+        HeadingPrefix::Hashes => "#".repeat(level),
+        HeadingPrefix::Numbers => {
+            let first = (1..=level)
+                .find(|level| header_format(theme, *level).prefix == HeadingPrefix::Numbers)
+                .unwrap_or(level);
+
+            numbers[first - 1..level]
+                .iter()
+                .map(u32::to_string)
+                .collect::<Vec<_>>()
+                .join(".")
+        }
+    };
+
+    let header_theme = header_theme(theme, level);
+    let mut text = Vec::new();
+    write_themed_text(
+        ElementType::WhitespacePaddedNode(&heading.children),
+        ctx,
+        Some(header_theme),
+        &mut text,
+    )?;
+
+    write!(writer, "\n {prefix}")?;
+    writer.write_all(&text)?;
+    writeln!(writer, " ")?;
+
+    if let Some(rule) = format.underline {
+        let width = visible_width(&prefix) + visible_width(&String::from_utf8_lossy(&text));
+        let rule = rule.to_string().repeat(width);
+
+        // The background of the heading is left out, so that the rule is a line.
+        let rule_theme = ElementTheme {
+            fg: header_theme.fg,
+            ..Default::default()
+        };
+
+        write!(writer, " ")?;
+        rule_theme.write_with_depth(|w| write!(w, "{rule}"), writer, ctx.colors())?;
+        writeln!(writer)?;
+    }
+
+    writeln!(writer)
+}
+
+fn header_theme(theme: &Theme, level: usize) -> &ElementTheme {
+    match level {
+        1 => &theme.header_1,
+        2 => &theme.header_2,
+        3 => &theme.header_3,
+        4 => &theme.header_4,
+        5 => &theme.header_5,
+        _ => &theme.header_6,
+    }
+}
+
+fn header_format(theme: &Theme, level: usize) -> HeadingFormat {
+    let formats = &theme.header_formats;
+    formats
+        .get(level - 1)
+        .or(formats.last())
+        .copied()
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::themes::get_dark_theme;
+    use crate::writer::write;
+    use crate::{ElementTheme, HeadingFormat, HeadingPrefix, TextStyle, Theme};
+
+    fn render(input: &str, theme: &Theme, is_writer_tty: bool) -> String {
+        let mut result = Vec::new();
+        write(input, theme, &mut result, is_writer_tty).unwrap();
+
+        String::from_utf8(result).unwrap()
+    }
+
+    #[test]
+    fn should_write_hashes_for_headers_5_and_6() {
+        let theme = get_dark_theme();
+        let result = render("##### Five\n\n###### Six", &theme, false);
+
+        assert_eq!(result, "\n ##### Five  \n\n\n ###### Six  \n\n");
+    }
+
+    #[test]
+    fn should_use_theme_of_each_level() {
+        let theme = Theme {
+            header_6: ElementTheme::new(Some("#F00"), None, TextStyle::Bold),
+            ..get_dark_theme()
+        };
+        let result = render("###### Six", &theme, true);
+
+        assert_eq!(result, "\n ######\u{1b}[1;38;2;255;0;0m Six \u{1b}[0m \n\n");
+    }
+
+    #[test]
+    fn should_number_headers_from_first_numbered_level() {
+        let theme = Theme {
+            header_formats: vec![
+                HeadingFormat::new(HeadingPrefix::None, None),
+                HeadingFormat::new(HeadingPrefix::Numbers, None),
+            ],
+            ..get_dark_theme()
+        };
+        let input = "# Title\n\n## One\n\n### A\n\n### B\n\n## Two\n\n#### Deep";
+        let result = render(input, &theme, false);
+
+        assert_eq!(
+            result,
+            "\n  Title  \n\n\n 1 One  \n\n\n 1.1 A  \n\n\n 1.2 B  \n\n\n 2 Two  \n\n\n 2.0.1 Deep  \n\n"
+        );
+    }
+
+    #[test]
+    fn should_draw_rule_under_header() {
+        let theme = Theme {
+            header_formats: vec![HeadingFormat::new(HeadingPrefix::Hashes, Some('='))],
+            ..get_dark_theme()
+        };
+        let result = render("## Title", &theme, false);
+
+        assert_eq!(result, "\n ## Title  \n =========\n\n");
+    }
+}
@@ -0,0 +1,219 @@
+use super::Context;
+use crate::ElementTheme;
+use markdown::mdast;
+use std::io::Write;
+use std::ops::Range;
+use std::sync::OnceLock;
+use syntect::parsing::{ParseState, ScopeStack, SyntaxSet};
+use syntect::util::LinesWithEndings;
+
+/// The kinds of tokens that can be themed using the [`crate::SyntaxTheme`].
+#[derive(Clone, Copy, PartialEq)]
+enum TokenKind {
+    Comment,
+    Keyword,
+    String,
+    Number,
+    Function,
+    TypeName,
+}
+
+/// Maps the scopes assigned by the syntax definitions to the kinds of tokens.
+/// The first prefix that matches is used, so more specific scopes come first.
+const TOKEN_SCOPES: [(&str, Option<TokenKind>); 13] = [
+    ("comment", Some(TokenKind::Comment)),
+    ("string", Some(TokenKind::String)),
+    ("constant.character", Some(TokenKind::String)),
+    ("constant", Some(TokenKind::Number)),
+    ("keyword.operator", None),
+    ("keyword", Some(TokenKind::Keyword)),
+    ("storage", Some(TokenKind::Keyword)),
+    ("entity.name.function", Some(TokenKind::Function)),
+    ("support.function", Some(TokenKind::Function)),
+    ("variable.function", Some(TokenKind::Function)),
+    ("entity.name", Some(TokenKind::TypeName)),
+    ("support.type", Some(TokenKind::TypeName)),
+    ("support.class", Some(TokenKind::TypeName)),
+];
+
+/// The syntax definitions are expensive to load, so they are only loaded once.
+fn syntax_set() -> &'static SyntaxSet {
+    static SYNTAX_SET: OnceLock<SyntaxSet> = OnceLock::new();
+    SYNTAX_SET.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+/// Gets the lines of the code block with every token colored using the syntax theme.
+///
+/// `None` is returned when the language of the code block is not known, so that the
+/// caller can fall back to writing the code block without highlighting.
+pub fn highlight_lines(
+    code: &mdast::Code,
+    ctx: &Context,
+) -> Result<Option<Vec<Vec<u8>>>, std::io::Error> {
+    let Some(tokens) = code
+        .lang
+        .as_deref()
+        .and_then(|lang| tokenize(lang, &code.value))
+    else {
+        return Ok(None);
+    };
+
+    let code_block = &ctx.theme.code_block;
+    let mut lines = vec![Vec::new()];
+    for (kind, range) in tokens {
+        let text = &code.value[range];
+        let token_theme = kind.map(|kind| token_theme(kind, ctx));
+
+        // Tokens only set the colors they care about and keep the rest from the code block.
+        let theme = ElementTheme {
+            fg: token_theme.and_then(|t| t.fg).or(code_block.fg),
+            bg: token_theme.and_then(|t| t.bg).or(code_block.bg),
+            style: token_theme.map_or(code_block.style, |t| t.style),
+        };
+
+        for (index, part) in text.split('\n').enumerate() {
+            if index > 0 {
+                lines.push(Vec::new());
+            }
+
+            if !part.is_empty() {
+                let line = lines.last_mut().expect("there is always a line");
+                theme.write_with_depth(|w| write!(w, "{part}"), line, ctx.colors())?;
+            }
+        }
+    }
+
+    Ok(Some(lines))
+}
+
+/// Splits the code into the byte ranges of its tokens. Neighbouring tokens of the same
+/// kind are joined together. Returns `None` if the language is not known.
+fn tokenize(lang: &str, code: &str) -> Option<Vec<(Option<TokenKind>, Range<usize>)>> {
+    let syntax_set = syntax_set();
+    let syntax = syntax_set.find_syntax_by_token(lang)?;
+
+    let mut tokens: Vec<(Option<TokenKind>, Range<usize>)> = Vec::new();
+    let mut push = |kind: Option<TokenKind>, range: Range<usize>| match tokens.last_mut() {
+        Some((last_kind, last_range)) if *last_kind == kind => last_range.end = range.end,
+        _ => tokens.push((kind, range)),
+    };
+
+    let mut state = ParseState::new(syntax);
+    let mut stack = ScopeStack::new();
+    let mut line_start = 0;
+
+    for line in LinesWithEndings::from(code) {
+        let ops = state.parse_line(line, syntax_set).ok()?;
+
+        let mut start = 0;
+        for (index, op) in ops {
+            if index > start {
+                push(token_kind(&stack), line_start + start..line_start + index);
+                start = index;
+            }
+
+            stack.apply(&op).ok()?;
+        }
+
+        if start < line.len() {
+            push(
+                token_kind(&stack),
+                line_start + start..line_start + line.len(),
+            );
+        }
+
+        line_start += line.len();
+    }
+
+    Some(tokens)
+}
+
+/// Gets the kind of token from the innermost scope that is themed.
+fn token_kind(stack: &ScopeStack) -> Option<TokenKind> {
+    stack
+        .as_slice()
+        .iter()
+        .rev()
+        .find_map(|scope| {
+            let name = scope.build_string();
+            TOKEN_SCOPES
+                .iter()
+                .find(|(prefix, _)| name.starts_with(prefix))
+                .map(|(_, kind)| *kind)
+        })
+        .flatten()
+}
+
+fn token_theme<'a>(kind: TokenKind, ctx: &Context<'a>) -> &'a ElementTheme {
+    let syntax = &ctx.theme.syntax;
+    match kind {
+        TokenKind::Comment => &syntax.comment,
+        TokenKind::Keyword => &syntax.keyword,
+        TokenKind::String => &syntax.string,
+        TokenKind::Number => &syntax.number,
+        TokenKind::Function => &syntax.function,
+        TokenKind::TypeName => &syntax.type_name,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::themes::get_dark_theme;
+    use crate::writer::write;
+    use crate::Color;
+    use colored::{Colorize, CustomColor};
+
+    fn render(input: &str, is_writer_tty: bool) -> String {
+        let theme = get_dark_theme();
+        let mut result = Vec::new();
+        write(input, &theme, &mut result, is_writer_tty).unwrap();
+
+        String::from_utf8(result).unwrap()
+    }
+
+    fn to_custom_color(color: Option<Color>) -> CustomColor {
+        let (r, g, b) = color.unwrap().to_rgb();
+        CustomColor::new(r, g, b)
+    }
+
+    #[test]
+    fn should_color_tokens_of_known_languages() {
+        let theme = get_dark_theme();
+        let result = render("```rust\nlet x = \"a\"; // b\n```", true);
+
+        let bg = to_custom_color(theme.code_block.bg);
+        let keyword = "let"
+            .custom_color(to_custom_color(theme.syntax.keyword.fg))
+            .on_custom_color(bg);
+        let string = "\"a\""
+            .custom_color(to_custom_color(theme.syntax.string.fg))
+            .on_custom_color(bg);
+
+        assert!(result.contains(&keyword.to_string()));
+        assert!(result.contains(&string.to_string()));
+        assert!(result.contains("// b"));
+    }
+
+    #[test]
+    fn should_fall_back_to_code_block_theme_for_unknown_languages() {
+        let theme = get_dark_theme();
+        let result = render("```notalanguage\nlet x\n```", true);
+
+        let expected = "let x"
+            .custom_color(to_custom_color(theme.code_block.fg))
+            .on_custom_color(to_custom_color(theme.code_block.bg));
+
+        assert!(result
+            .lines()
+            .nth(2)
+            .unwrap()
+            .contains(&expected.to_string()));
+    }
+
+    #[test]
+    fn should_not_highlight_if_not_tty() {
+        let result = render("```rust\nfn main() {\n}\n```", false);
+
+        assert_eq!(result, "\nfn main() {\n}\n");
+    }
+}
@@ -0,0 +1,585 @@
+use super::script::{subscript, superscript};
+use super::{image, link, write_raw_text, write_rule, write_themed_text, Context, ElementType};
+use crate::{ElementTheme, TextStyle, UnknownHtml};
+use markdown::mdast;
+use std::io::Write;
+
+/// Tags that are left out without changing how their content is written.
+const STRIPPED_TAGS: [&str; 17] = [
+    "abbr", "article", "big", "center", "details", "div", "font", "footer", "header", "nav", "p",
+    "picture", "pre", "section", "small", "source", "span",
+];
+
+/// Tags whose elements are left out together with their content when unknown HTML is
+/// stripped, as their content is code rather than text.
+const DROPPED_TAGS: [&str; 2] = ["script", "style"];
+
+/// Tags that style the nodes up to their closing tag.
+const STYLED_TAGS: [&str; 16] = [
+    "a", "b", "code", "del", "em", "i", "ins", "kbd", "s", "strike", "strong", "sub", "summary",
+    "sup", "tt", "u",
+];
+
+/// The kinds of HTML tags.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TagKind {
+    /// An opening tag. i.e `<b>`
+    Open,
+
+    /// A closing tag. i.e `</b>`
+    Close,
+
+    /// A tag that has no content, either because it ends with `/>` or because the
+    /// element is always empty. i.e `<br>`
+    Empty,
+
+    /// A comment, declaration or processing instruction. i.e `<!-- comment -->`
+    Comment,
+}
+
+/// An HTML tag with a lower case name.
+#[derive(Debug, PartialEq)]
+struct Tag {
+    name: String,
+    kind: TagKind,
+    attributes: Vec<(String, String)>,
+}
+
+impl Tag {
+    /// Parses a single tag. Returns `None` if the text is not a tag.
+    fn parse(text: &str) -> Option<Tag> {
+        let text = text.trim();
+        let inner = text.strip_prefix('<')?.strip_suffix('>')?;
+        if inner.starts_with('!') || inner.starts_with('?') {
+            return Some(Tag {
+                name: String::new(),
+                kind: TagKind::Comment,
+                attributes: Vec::new(),
+            });
+        }
+
+        let (inner, is_close) = match inner.strip_prefix('/') {
+            Some(inner) => (inner, true),
+            None => (inner, false),
+        };
+        let (inner, is_self_closing) = match inner.strip_suffix('/') {
+            Some(inner) => (inner, true),
+            None => (inner, false),
+        };
+
+        let name_end = inner
+            .find(|c: char| c.is_ascii_whitespace())
+            .unwrap_or(inner.len());
+        let name = inner[..name_end].to_ascii_lowercase();
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return None;
+        }
+
+        let kind = match (is_close, is_self_closing) {
+            (true, _) => TagKind::Close,
+            (false, true) => TagKind::Empty,
+            _ if matches!(name.as_str(), "br" | "hr" | "img" | "source" | "wbr") => TagKind::Empty,
+            _ => TagKind::Open,
+        };
+
+        Some(Tag {
+            name,
+            kind,
+            attributes: parse_attributes(&inner[name_end..]),
+        })
+    }
+
+    /// Gets the value of the attribute with the name.
+    fn attribute(&self, name: &str) -> Option<&str> {
+        (self.attributes.iter())
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Indicates whether the tag is left out when it is written on its own, without
+    /// anything to style.
+    fn writes_nothing(&self, ctx: &Context) -> bool {
+        match self.kind {
+            TagKind::Comment => true,
+            _ if matches!(self.name.as_str(), "br" | "hr" | "img") => false,
+            _ if is_known(&self.name) => true,
+            _ => ctx.unknown_html == UnknownHtml::Strip,
+        }
+    }
+}
+
+/// Writes an inline HTML tag, together with the nodes up to its closing tag when it
+/// styles them. Returns the index of the node after the last node that was written.
+pub fn write_inline_html(
+    children: &[mdast::Node],
+    index: usize,
+    ctx: &Context,
+    writer: &mut impl Write,
+) -> Result<usize, std::io::Error> {
+    let mdast::Node::Html(html) = &children[index] else {
+        return Ok(index);
+    };
+
+    if let Some(end) = dropped_element_end(children, index, ctx) {
+        return Ok(end);
+    }
+
+    let Some(tag) = Tag::parse(&html.value) else {
+        write_unknown(&html.value, ctx, writer)?;
+        return Ok(index + 1);
+    };
+
+    match (tag.kind, tag.name.as_str()) {
+        (TagKind::Comment, _) => {}
+        (_, "br") => writeln!(writer)?,
+        (_, "hr") => write_rule(ctx, writer)?,
+        (TagKind::Open | TagKind::Empty, "img") => write_img(&tag, ctx, writer)?,
+        (TagKind::Open, name) if STYLED_TAGS.contains(&name) => {
+            if let Some(close) = find_closing_tag(children, index, name) {
+                write_styled(&tag, &children[index + 1..close], ctx, writer)?;
+                return Ok(close + 1);
+            }
+        }
+        (_, name) if is_known(name) => {}
+        _ => write_unknown(&html.value, ctx, writer)?,
+    }
+
+    Ok(index + 1)
+}
+
+/// Writes an HTML block by styling its tags in the same way as inline HTML.
+///
+/// Lines that are left empty once the tags are taken out are left out, and the
+/// indentation that the rest have in common is taken away. The content of `<pre>`
+/// elements is written as it is, without being wrapped.
+pub fn write_html_block(
+    html: &mdast::Html,
+    ctx: &Context,
+    writer: &mut impl Write,
+) -> Result<(), std::io::Error> {
+    let mut parts = Vec::new();
+    for (is_pre, html) in split_pre(&html.value) {
+        let mut rendered = Vec::new();
+        write_raw_text(&tokenize(html), ctx, &mut rendered)?;
+        let rendered = String::from_utf8_lossy(&rendered);
+
+        let text = match is_pre {
+            true => rendered.trim_matches('\n').to_string(),
+            false => {
+                let text = dedent(&rendered);
+                match ctx.width {
+                    Some(width) => super::wrap::wrap(&text, width),
+                    None => text,
+                }
+            }
+        };
+
+        if !text.is_empty() {
+            parts.push(text);
+        }
+    }
+
+    write!(writer, "{}", parts.join("\n"))
+}
+
+/// Indicates whether nothing would be written for the HTML block, such as when it is a
+/// comment or the closing tag of a `<details>` element.
+pub fn is_blank(html: &mdast::Html, ctx: &Context) -> bool {
+    let tokens = tokenize(&html.value);
+    let mut index = 0;
+    while index < tokens.len() {
+        if let Some(end) = dropped_element_end(&tokens, index, ctx) {
+            index = end;
+            continue;
+        }
+
+        let is_blank = match &tokens[index] {
+            mdast::Node::Html(html) => {
+                Tag::parse(&html.value).is_some_and(|tag| tag.writes_nothing(ctx))
+            }
+            mdast::Node::Text(text) => text.value.trim().is_empty(),
+            _ => false,
+        };
+
+        if !is_blank {
+            return false;
+        }
+
+        index += 1;
+    }
+
+    true
+}
+
+/// Gets the index of the node after the element that is opened at the index, when it is
+/// an element that is left out with its content, such as `<script>`. An element that is
+/// not closed runs to the end of the nodes.
+fn dropped_element_end(children: &[mdast::Node], index: usize, ctx: &Context) -> Option<usize> {
+    let mdast::Node::Html(html) = &children[index] else {
+        return None;
+    };
+
+    let tag = Tag::parse(&html.value)?;
+    if ctx.unknown_html != UnknownHtml::Strip
+        || tag.kind != TagKind::Open
+        || !DROPPED_TAGS.contains(&tag.name.as_str())
+    {
+        return None;
+    }
+
+    Some(find_closing_tag(children, index, &tag.name).map_or(children.len(), |close| close + 1))
+}
+
+/// Splits the HTML into the `<pre>` elements and the HTML between them. Each part is
+/// returned with whether it is a `<pre>` element.
+fn split_pre(html: &str) -> Vec<(bool, &str)> {
+    // Lower casing ASCII keeps the byte offsets of the characters the same.
+    let lower = html.to_ascii_lowercase();
+    let mut parts = Vec::new();
+    let mut start = 0;
+    while let Some(open) = (lower[start..].match_indices("<pre"))
+        .map(|(offset, _)| start + offset)
+        .find(|open| lower[open + 4..].starts_with(['>', ' ', '\t', '\n']))
+    {
+        let Some(close) = lower[open..].find("</pre>").map(|offset| open + offset + 6) else {
+            break;
+        };
+
+        parts.push((false, &html[start..open]));
+        parts.push((true, &html[open..close]));
+        start = close;
+    }
+
+    parts.push((false, &html[start..]));
+    parts
+}
+
+/// Leaves out the blank lines of the text, and takes away the indentation that the rest
+/// of the lines have in common.
+fn dedent(text: &str) -> String {
+    let lines: Vec<&str> = (text.lines())
+        .filter(|line| !line.trim().is_empty())
+        .collect();
+    let indent = (lines.iter())
+        .map(|line| line.len() - line.trim_start_matches([' ', '\t']).len())
+        .min()
+        .unwrap_or(0);
+
+    (lines.iter())
+        .map(|line| &line[indent..])
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Writes the nodes in the element of the tag with the style of the tag.
+fn write_styled(
+    tag: &Tag,
+    children: &[mdast::Node],
+    ctx: &Context,
+    writer: &mut impl Write,
+) -> Result<(), std::io::Error> {
+    let theme = ctx.theme;
+    let underlined = ElementTheme::new(None, None, TextStyle::Underlined);
+    let (input, style) = match tag.name.as_str() {
+        "a" => {
+            let Some(href) = tag.attribute("href") else {
+                return write_raw_text(children, ctx, writer);
+            };
+
+            let link = mdast::Link {
+                children: children.to_vec(),
+                position: None,
+                url: href.to_string(),
+                title: tag.attribute("title").map(str::to_string),
+            };
+
+            return link::write_link(&link, ctx, writer);
+        }
+        "sub" | "sup" => {
+            let script = plain_text(children)
+                .filter(|_| ctx.unicode)
+                .and_then(|text| match tag.name.as_str() {
+                    "sub" => subscript(&text),
+                    _ => superscript(&text),
+                });
+
+            return match script {
+                Some(script) => write!(writer, "{script}"),
+                None => write_raw_text(children, ctx, writer),
+            };
+        }
+        "summary" => {
+            write!(writer, "{} ", if ctx.unicode { '▸' } else { '>' })?;
+            (ElementType::Nodes(children), &theme.html_summary)
+        }
+        "kbd" => (ElementType::WhitespacePaddedNode(children), &theme.html_kbd),
+        "code" | "tt" => (
+            ElementType::WhitespacePaddedNode(children),
+            &theme.code_inline,
+        ),
+        "b" | "strong" => (ElementType::Nodes(children), &theme.strong),
+        "i" | "em" => (ElementType::Nodes(children), &theme.emphasis),
+        "s" | "del" | "strike" => (ElementType::Nodes(children), &theme.delete),
+        _ => (ElementType::Nodes(children), &underlined),
+    };
+
+    write_themed_text(input, ctx, Some(style), writer)
+}
+
+/// Draws the image of an `<img>` tag in the same way as a markdown image.
+fn write_img(tag: &Tag, ctx: &Context, writer: &mut impl Write) -> Result<(), std::io::Error> {
+    let Some(src) = tag.attribute("src") else {
+        return Ok(());
+    };
+
+    let image = mdast::Image {
+        position: None,
+        alt: tag.attribute("alt").unwrap_or_default().to_string(),
+        url: src.to_string(),
+        title: tag.attribute("title").map(str::to_string),
+    };
+
+    image::write_image(&image, ctx, writer)
+}
+
+/// Writes a tag that is not known in the way that the options ask for.
+fn write_unknown(html: &str, ctx: &Context, writer: &mut impl Write) -> Result<(), std::io::Error> {
+    match ctx.unknown_html {
+        UnknownHtml::Strip => Ok(()),
+        UnknownHtml::Raw => write!(writer, "{html}"),
+        UnknownHtml::Dimmed => write_themed_text(
+            ElementType::Text(html),
+            ctx,
+            Some(&ctx.theme.html_tag),
+            writer,
+        ),
+    }
+}
+
+/// Indicates whether the tag is styled or left out, rather than being unknown.
+fn is_known(name: &str) -> bool {
+    STRIPPED_TAGS.contains(&name)
+        || STYLED_TAGS.contains(&name)
+        || matches!(name, "br" | "hr" | "img" | "wbr")
+}
+
+/// Finds the index of the tag that closes the element opened at the index, taking
+/// nested elements with the same name into account.
+fn find_closing_tag(children: &[mdast::Node], index: usize, name: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (offset, child) in children[index + 1..].iter().enumerate() {
+        let mdast::Node::Html(html) = child else {
+            continue;
+        };
+
+        match Tag::parse(&html.value) {
+            Some(tag) if tag.name == name && tag.kind == TagKind::Open => depth += 1,
+            Some(tag) if tag.name == name && tag.kind == TagKind::Close => {
+                if depth == 0 {
+                    return Some(index + 1 + offset);
+                }
+
+                depth -= 1;
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Splits HTML into a node for every tag and a node for the text between them, in the
+/// same way as markdown splits inline HTML.
+fn tokenize(html: &str) -> Vec<mdast::Node> {
+    let text = |value: &str| {
+        mdast::Node::Text(mdast::Text {
+            value: value.to_string(),
+            position: None,
+        })
+    };
+
+    let mut nodes = Vec::new();
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        let tag = &rest[start..];
+        let end = match tag.starts_with("<!--") {
+            true => tag.find("-->").map(|end| end + 3),
+            false => tag.find('>').map(|end| end + 1),
+        };
+
+        let Some(end) = end.filter(|end| Tag::parse(&tag[..*end]).is_some()) else {
+            nodes.push(text(&rest[..start + 1]));
+            rest = &rest[start + 1..];
+            continue;
+        };
+
+        if start > 0 {
+            nodes.push(text(&rest[..start]));
+        }
+
+        nodes.push(mdast::Node::Html(mdast::Html {
+            value: tag[..end].to_string(),
+            position: None,
+        }));
+        rest = &tag[end..];
+    }
+
+    if !rest.is_empty() {
+        nodes.push(text(rest));
+    }
+
+    nodes
+}
+
+/// Parses the attributes of a tag, such as `src="a.png" alt='A' width=10 hidden`.
+fn parse_attributes(text: &str) -> Vec<(String, String)> {
+    let mut attributes = Vec::new();
+    let mut rest = text.trim_start();
+    while !rest.is_empty() {
+        let name_end = rest
+            .find(|c: char| c.is_ascii_whitespace() || c == '=')
+            .unwrap_or(rest.len());
+        let name = rest[..name_end].to_ascii_lowercase();
+        rest = rest[name_end..].trim_start();
+
+        let mut value = "";
+        if let Some(after) = rest.strip_prefix('=') {
+            let after = after.trim_start();
+            let (quote, after) = match after.chars().next() {
+                Some(quote @ ('"' | '\'')) => (Some(quote), &after[1..]),
+                _ => (None, after),
+            };
+
+            let value_end = match quote {
+                Some(quote) => after.find(quote),
+                None => after.find(|c: char| c.is_ascii_whitespace()),
+            }
+            .unwrap_or(after.len());
+
+            value = &after[..value_end];
+            rest = match quote {
+                Some(_) if value_end < after.len() => &after[value_end + 1..],
+                _ => &after[value_end..],
+            };
+        }
+
+        if !name.is_empty() {
+            attributes.push((name, value.to_string()));
+        }
+
+        rest = rest.trim_start();
+    }
+
+    attributes
+}
+
+/// Gets the text of the nodes if they are only text.
+fn plain_text(children: &[mdast::Node]) -> Option<String> {
+    children
+        .iter()
+        .map(|child| match child {
+            mdast::Node::Text(text) => Some(text.value.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::renderer::RenderOptions;
+    use crate::themes::get_dark_theme;
+    use crate::writer::{render_with_options, test_options};
+    use crate::UnknownHtml;
+
+    fn render(input: &str, unknown_html: UnknownHtml, is_writer_tty: bool) -> String {
+        let options = RenderOptions {
+            unicode: true,
+            unknown_html,
+            ..test_options()
+        };
+
+        render_with_options(input, &get_dark_theme(), &options, is_writer_tty)
+    }
+
+    #[test]
+    fn should_style_known_tags() {
+        let input = "Press <kbd>Ctrl</kbd>+<kbd>C</kbd> to <ins>copy</ins>.";
+        let result = render(input, UnknownHtml::Strip, true);
+        let kbd = "\u{1b}[1;48;2;78;78;78;38;2;228;228;228m";
+
+        assert_eq!(
+            result,
+            format!("Press {kbd} Ctrl \u{1b}[0m+{kbd} C \u{1b}[0m to \u{1b}[4mcopy\u{1b}[0m.")
+        );
+    }
+
+    #[test]
+    fn should_write_sub_and_superscripts() {
+        let input = "H<sub>2</sub>O, x<sup>2n</sup> and <sup>Quit</sup>";
+        let result = render(input, UnknownHtml::Strip, false);
+
+        assert_eq!(result, "H₂O, x²ⁿ and Quit");
+    }
+
+    #[test]
+    fn should_break_lines_and_strip_comments() {
+        let result = render("One<br>two <!-- hidden -->three", UnknownHtml::Strip, false);
+
+        assert_eq!(result, "One\ntwo three");
+    }
+
+    #[test]
+    fn should_write_unknown_tags_by_policy() {
+        let input = "<foo>bar</foo>";
+
+        assert_eq!(render(input, UnknownHtml::Strip, true), "bar");
+        assert_eq!(render(input, UnknownHtml::Raw, true), "<foo>bar</foo>");
+        assert_eq!(
+            render(input, UnknownHtml::Dimmed, true),
+            "\u{1b}[2m<foo>\u{1b}[0mbar\u{1b}[2m</foo>\u{1b}[0m"
+        );
+    }
+
+    #[test]
+    fn should_write_summary_of_details() {
+        let input = "Para\n\n<details>\n<summary>More</summary>\n\nHidden\n\n</details>\n\nAfter";
+        let result = render(input, UnknownHtml::Strip, false);
+
+        assert_eq!(result, "Para\n\n▸ More\n\nHidden\n\nAfter");
+    }
+
+    #[test]
+    fn should_write_images_and_links_of_html_blocks() {
+        let input = "<p align=\"center\">\n  <a href=\"http://a.com\"><img src=\"logo.png\" alt='Logo' width=100></a>\n</p>";
+        let result = render(input, UnknownHtml::Strip, false);
+
+        assert_eq!(result, "[image: Logo](logo.png) (http://a.com)");
+    }
+
+    #[test]
+    fn should_leave_out_blocks_of_comments() {
+        let result = render("One\n\n<!-- comment -->\n\nTwo", UnknownHtml::Strip, false);
+
+        assert_eq!(result, "One\n\nTwo");
+    }
+
+    #[test]
+    fn should_drop_scripts_and_styles_when_stripping() {
+        let input = "<script>\nalert(1);\n</script>\n\nOne <style>p { color: red }</style>two";
+
+        assert_eq!(render(input, UnknownHtml::Strip, false), "One two");
+        assert_eq!(
+            render("<style>p {}</style>", UnknownHtml::Raw, false),
+            "<style>p {}</style>"
+        );
+    }
+
+    #[test]
+    fn should_keep_indentation_of_pre_blocks() {
+        let input =
+            "<div>\n  <b>Top</b>\n    Nested\n<pre>\nfn main() {\n    run();\n}\n</pre>\n</div>";
+        let result = render(input, UnknownHtml::Strip, false);
+
+        assert_eq!(result, "Top\n  Nested\nfn main() {\n    run();\n}");
+    }
+}
@@ -0,0 +1,461 @@
+use super::{write_themed_text, Context, ElementType, T_ESC};
+use crate::ImageProtocol;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use markdown::mdast;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Kitty only accepts up to 4096 bytes of base64 data in every escape sequence.
+const KITTY_CHUNK_SIZE: usize = 4096;
+
+/// The width of a cell in pixels, used to convert the width of images to columns.
+/// Terminals do not report it, so the width of a cell in a common font size is used.
+const CELL_WIDTH: u32 = 10;
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// Draws the image if it is a local file that the terminal can draw, and writes a
+/// `[image: alt text](url)` placeholder otherwise.
+///
+/// Only regular files up to the size limit are read, so that a path such as `/dev/zero`
+/// cannot hang the renderer. Images that are wider than the wrap width are scaled down
+/// to it, and are followed by a line break.
+pub fn write_image(
+    image: &mdast::Image,
+    ctx: &Context,
+    writer: &mut impl std::io::Write,
+) -> Result<(), std::io::Error> {
+    if ctx.is_writer_tty {
+        let graphic = local_path(&image.url, ctx.base_dir)
+            .and_then(|path| read_image(&path, ctx.max_image_bytes))
+            .and_then(|data| encode(&data, ctx));
+
+        if let Some(graphic) = graphic {
+            return writeln!(writer, "{graphic}");
+        }
+    }
+
+    write_placeholder(image, ctx, writer)
+}
+
+/// Draws an image that uses a definition in the same way as an image.
+pub fn write_image_reference(
+    reference: &mdast::ImageReference,
+    ctx: &Context,
+    writer: &mut impl std::io::Write,
+) -> Result<(), std::io::Error> {
+    // Markdown is only parsed as a reference when its definition exists.
+    let Some(destination) = ctx.definitions.get(&reference.identifier) else {
+        return write!(writer, "{}", reference.alt);
+    };
+
+    let image = mdast::Image {
+        alt: reference.alt.clone(),
+        url: destination.url,
+        title: destination.title,
+        position: None,
+    };
+
+    write_image(&image, ctx, writer)
+}
+
+fn write_placeholder(
+    image: &mdast::Image,
+    ctx: &Context,
+    writer: &mut impl std::io::Write,
+) -> Result<(), std::io::Error> {
+    let url = &image.url;
+    let placeholder = match image.alt.as_str() {
+        "" => format!("[image]({url})"),
+        alt => format!("[image: {alt}]({url})"),
+    };
+
+    let is_hyperlink = ctx.is_writer_tty && ctx.hyperlinks && !url.is_empty();
+    if is_hyperlink {
+        write!(writer, "{T_ESC}]8;;{url}{T_ESC}\\")?;
+    }
+
+    write_themed_text(
+        ElementType::Text(&placeholder),
+        ctx,
+        Some(&ctx.theme.image),
+        writer,
+    )?;
+
+    if is_hyperlink {
+        write!(writer, "{T_ESC}]8;;{T_ESC}\\")?;
+    }
+
+    Ok(())
+}
+
+/// Gets the path of the image if it is a local file. Relative paths are resolved from
+/// the base directory.
+fn local_path(url: &str, base_dir: Option<&Path>) -> Option<PathBuf> {
+    let path = url.strip_prefix("file://").unwrap_or(url);
+    if path.is_empty() || path.contains("://") || path.starts_with("data:") {
+        return None;
+    }
+
+    let path = Path::new(path);
+    match base_dir {
+        Some(base_dir) if path.is_relative() => Some(base_dir.join(path)),
+        _ => Some(path.to_path_buf()),
+    }
+}
+
+/// Reads the image file, or returns `None` if it is not a regular file or is larger than
+/// the limit. At most one byte more than the limit is read, in case the file grows.
+fn read_image(path: &Path, max_bytes: u64) -> Option<Vec<u8>> {
+    let metadata = std::fs::metadata(path).ok()?;
+    if !metadata.is_file() || metadata.len() > max_bytes {
+        return None;
+    }
+
+    let mut data = Vec::with_capacity(metadata.len() as usize);
+    std::fs::File::open(path)
+        .ok()?
+        .take(max_bytes.saturating_add(1))
+        .read_to_end(&mut data)
+        .ok()?;
+
+    (data.len() as u64 <= max_bytes).then_some(data)
+}
+
+/// Encodes the image as the escape sequences of the protocol.
+/// Returns `None` if the protocol cannot draw the image.
+fn encode(data: &[u8], ctx: &Context) -> Option<String> {
+    let columns = columns(data, ctx.width);
+    match ctx.images {
+        ImageProtocol::Kitty => kitty(data, columns),
+        ImageProtocol::Iterm2 => Some(iterm2(data, columns)),
+        ImageProtocol::Sixel => sixel(data, columns),
+        ImageProtocol::Placeholder => None,
+    }
+}
+
+/// Gets the number of columns that the image is drawn across, which is its own width or
+/// the wrap width, whichever is smaller, so that images are only ever scaled down.
+/// Returns `None` if the width of the image is not known.
+fn columns(data: &[u8], width: Option<usize>) -> Option<usize> {
+    let natural = pixel_width(data)?.div_ceil(CELL_WIDTH).max(1) as usize;
+    Some(width.map_or(natural, |width| natural.min(width)))
+}
+
+/// Gets the width of the image in pixels. PNGs and GIFs are read from their header, and
+/// other formats need the `images` feature.
+fn pixel_width(data: &[u8]) -> Option<u32> {
+    if data.starts_with(PNG_SIGNATURE) && data.get(12..16) == Some(b"IHDR") {
+        return Some(u32::from_be_bytes(data.get(16..20)?.try_into().ok()?));
+    }
+
+    if data.starts_with(b"GIF8") {
+        return Some(u16::from_le_bytes(data.get(6..8)?.try_into().ok()?).into());
+    }
+
+    decoded_pixel_width(data)
+}
+
+#[cfg(feature = "images")]
+fn decoded_pixel_width(data: &[u8]) -> Option<u32> {
+    let reader = image::ImageReader::new(std::io::Cursor::new(data))
+        .with_guessed_format()
+        .ok()?;
+
+    reader.into_dimensions().ok().map(|(width, _)| width)
+}
+
+#[cfg(not(feature = "images"))]
+fn decoded_pixel_width(_data: &[u8]) -> Option<u32> {
+    None
+}
+
+/// Encodes the image for the Kitty graphics protocol. PNGs are sent as they are, and other
+/// formats are decoded to RGBA pixels. The image is drawn across the columns, or at its
+/// own size when they are not known.
+fn kitty(data: &[u8], columns: Option<usize>) -> Option<String> {
+    let (mut params, payload) = if data.starts_with(PNG_SIGNATURE) {
+        ("a=T,f=100,q=2".to_string(), STANDARD.encode(data))
+    } else {
+        kitty_rgba(data)?
+    };
+
+    if let Some(columns) = columns {
+        params.push_str(&format!(",c={columns}"));
+    }
+
+    let chunks: Vec<&[u8]> = payload.as_bytes().chunks(KITTY_CHUNK_SIZE).collect();
+    let mut result = String::with_capacity(payload.len() + chunks.len() * 32);
+    for (index, chunk) in chunks.iter().enumerate() {
+        let more = u8::from(index + 1 < chunks.len());
+
+        // The payload is base64, so every chunk is ASCII.
+        let chunk = std::str::from_utf8(chunk).ok()?;
+        if index == 0 {
+            result.push_str(&format!("{T_ESC}_G{params},m={more};{chunk}{T_ESC}\\"));
+        } else {
+            result.push_str(&format!("{T_ESC}_Gm={more};{chunk}{T_ESC}\\"));
+        }
+    }
+
+    Some(result)
+}
+
+#[cfg(feature = "images")]
+fn kitty_rgba(data: &[u8]) -> Option<(String, String)> {
+    let image = image::load_from_memory(data).ok()?.to_rgba8();
+    let params = format!("a=T,f=32,s={},v={},q=2", image.width(), image.height());
+
+    Some((params, STANDARD.encode(image.as_raw())))
+}
+
+#[cfg(not(feature = "images"))]
+fn kitty_rgba(_data: &[u8]) -> Option<(String, String)> {
+    None
+}
+
+/// Encodes the image for the inline image protocol of iTerm2, which decodes the image
+/// itself. The image is drawn across the columns, or at its own size when they are not
+/// known.
+fn iterm2(data: &[u8], columns: Option<usize>) -> String {
+    let width = columns.map_or("auto".to_string(), |columns| columns.to_string());
+
+    format!(
+        "{T_ESC}]1337;File=inline=1;size={};width={width};preserveAspectRatio=1:{}\u{7}",
+        data.len(),
+        STANDARD.encode(data)
+    )
+}
+
+/// Encodes the image as Sixel graphics. Images that are wider than the columns are scaled
+/// down, and the colors are reduced to a palette of 216 colors.
+#[cfg(feature = "images")]
+fn sixel(data: &[u8], columns: Option<usize>) -> Option<String> {
+    use std::collections::BTreeMap;
+    use std::fmt::Write;
+
+    let mut image = image::load_from_memory(data).ok()?;
+    if let Some(columns) = columns {
+        let max_width = (columns as u32).saturating_mul(CELL_WIDTH).max(1);
+        if image.width() > max_width {
+            image = image.resize(max_width, u32::MAX, image::imageops::FilterType::Triangle);
+        }
+    }
+
+    let image = image.to_rgba8();
+    let (width, height) = image.dimensions();
+
+    // `P2=1` leaves the pixels that are not drawn transparent.
+    let mut result = format!("{T_ESC}P0;1;0q\"1;1;{width};{height}");
+    for index in 0..216u32 {
+        let (r, g, b) = (index / 36, index / 6 % 6, index % 6);
+        let _ = write!(result, "#{index};2;{};{};{}", r * 20, g * 20, b * 20);
+    }
+
+    // Every row of sixels draws 6 rows of pixels. Every color in the row is drawn
+    // separately, and `$` moves back to the start of the row.
+    for top in (0..height).step_by(6) {
+        let mut colors: BTreeMap<u32, Vec<u8>> = BTreeMap::new();
+        for x in 0..width {
+            for bit in 0..6 {
+                let y = top + bit;
+                if y >= height {
+                    break;
+                }
+
+                let [r, g, b, a] = image.get_pixel(x, y).0;
+                if a < 128 {
+                    continue;
+                }
+
+                let level = |value: u8| value as u32 * 6 / 256;
+                let color = level(r) * 36 + level(g) * 6 + level(b);
+                colors
+                    .entry(color)
+                    .or_insert_with(|| vec![0; width as usize])[x as usize] |= 1 << bit;
+            }
+        }
+
+        for (index, (color, sixels)) in colors.iter().enumerate() {
+            if index > 0 {
+                result.push('$');
+            }
+
+            let _ = write!(result, "#{color}");
+            for run in sixels.chunk_by(|a, b| a == b) {
+                let c = (63 + run[0]) as char;
+                if run.len() > 3 {
+                    let _ = write!(result, "!{}{c}", run.len());
+                } else {
+                    result.extend(std::iter::repeat_n(c, run.len()));
+                }
+            }
+        }
+
+        result.push('-');
+    }
+
+    result.push_str(&format!("{T_ESC}\\"));
+    Some(result)
+}
+
+#[cfg(not(feature = "images"))]
+fn sixel(_data: &[u8], _columns: Option<usize>) -> Option<String> {
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::renderer::RenderOptions;
+    use crate::themes::get_dark_theme;
+    use crate::writer::{render_with_options, test_options};
+    use colored::Colorize;
+
+    fn render(input: &str, images: ImageProtocol, base_dir: &Path, tty: bool) -> String {
+        let theme = get_dark_theme();
+        let options = RenderOptions {
+            width: Some(40),
+            hyperlinks: true,
+            images,
+            base_dir: Some(base_dir.to_path_buf()),
+            max_image_bytes: 8192,
+            ..test_options()
+        };
+
+        render_with_options(input, &theme, &options, tty)
+    }
+
+    /// Creates a directory with a file that starts like a PNG that is 1000 pixels wide.
+    /// Only Sixel decodes images.
+    fn image_dir(name: &str, size: usize) -> PathBuf {
+        image_dir_with_width(name, 1000, size)
+    }
+
+    fn image_dir_with_width(name: &str, pixel_width: u32, size: usize) -> PathBuf {
+        let mut dir = std::env::temp_dir();
+        dir.push(format!("markterm-images-{name}"));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let mut data = PNG_SIGNATURE.to_vec();
+        data.extend_from_slice(b"\0\0\0\x0dIHDR");
+        data.extend_from_slice(&pixel_width.to_be_bytes());
+        data.resize(size.max(data.len()), b'a');
+        std::fs::write(dir.join("image.png"), data).unwrap();
+
+        dir
+    }
+
+    #[test]
+    fn should_draw_pngs_with_kitty() {
+        let dir = image_dir("kitty", 12);
+        let result = render("![alt](image.png)", ImageProtocol::Kitty, &dir, true);
+
+        let payload = STANDARD.encode(std::fs::read(dir.join("image.png")).unwrap());
+        let expected = format!("{T_ESC}_Ga=T,f=100,q=2,c=40,m=0;{payload}{T_ESC}\\\n");
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn should_split_kitty_images_into_chunks() {
+        let dir = image_dir("kitty-chunks", 4000);
+        let result = render("![alt](image.png)", ImageProtocol::Kitty, &dir, true);
+
+        assert_eq!(result.matches(&format!("{T_ESC}_G")).count(), 2);
+        assert!(result.contains(",m=1;"));
+        assert!(result.contains(&format!("{T_ESC}_Gm=0;")));
+    }
+
+    #[test]
+    fn should_draw_images_with_iterm2() {
+        let dir = image_dir("iterm2", 24);
+        let result = render("![alt](image.png)", ImageProtocol::Iterm2, &dir, true);
+
+        let payload = STANDARD.encode(std::fs::read(dir.join("image.png")).unwrap());
+        let expected = format!(
+            "{T_ESC}]1337;File=inline=1;size=24;width=40;preserveAspectRatio=1:{payload}\u{7}\n"
+        );
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn should_not_scale_images_up_to_wrap_width() {
+        let dir = image_dir_with_width("small", 16, 32);
+        let kitty = render("![alt](image.png)", ImageProtocol::Kitty, &dir, true);
+        let iterm2 = render("![alt](image.png)", ImageProtocol::Iterm2, &dir, true);
+
+        assert!(kitty.contains(",c=2,"));
+        assert!(iterm2.contains(";width=2;"));
+    }
+
+    #[cfg(feature = "images")]
+    #[test]
+    fn should_draw_images_with_sixel() {
+        let dir = image_dir("sixel", 0);
+        image::RgbaImage::from_pixel(2, 2, image::Rgba([255, 0, 0, 255]))
+            .save(dir.join("image.png"))
+            .unwrap();
+
+        let result = render("![alt](image.png)", ImageProtocol::Sixel, &dir, true);
+
+        assert!(result.starts_with(&format!("{T_ESC}P0;1;0q\"1;1;2;2")));
+        assert!(result.ends_with(&format!("#180BB-{T_ESC}\\\n")));
+    }
+
+    #[test]
+    fn should_write_placeholder_with_hyperlink_for_remote_images() {
+        let dir = image_dir("remote", 12);
+        let result = render(
+            "![alt](http://a.com/b.png)",
+            ImageProtocol::Kitty,
+            &dir,
+            true,
+        );
+
+        let theme = get_dark_theme();
+        let (r, g, b) = theme.image.fg.unwrap().to_rgb();
+        let expected = format!(
+            "{T_ESC}]8;;http://a.com/b.png{T_ESC}\\{}{T_ESC}]8;;{T_ESC}\\",
+            "[image: alt](http://a.com/b.png)"
+                .custom_color(colored::CustomColor::new(r, g, b))
+                .italic()
+        );
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn should_write_placeholder_for_missing_files() {
+        let dir = image_dir("missing", 12);
+        let result = render("![](missing.png)", ImageProtocol::Kitty, &dir, false);
+
+        assert_eq!(result, "[image](missing.png)");
+    }
+
+    #[test]
+    fn should_write_placeholder_for_files_over_limit() {
+        let dir = image_dir("large", 8193);
+        let result = render("![alt](image.png)", ImageProtocol::Kitty, &dir, true);
+
+        assert!(result.contains("[image: alt](image.png)"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn should_write_placeholder_for_files_that_are_not_regular() {
+        let dir = image_dir("device", 12);
+        let result = render("![alt](/dev/zero)", ImageProtocol::Kitty, &dir, true);
+
+        assert!(result.contains("[image: alt](/dev/zero)"));
+    }
+
+    #[test]
+    fn should_write_placeholder_if_not_tty() {
+        let dir = image_dir("not-tty", 12);
+        let result = render("![alt](image.png)", ImageProtocol::Kitty, &dir, false);
+
+        assert_eq!(result, "[image: alt](image.png)");
+    }
+}
@@ -0,0 +1,269 @@
+use super::{write_themed_text, Context, ElementType, T_ESC};
+use crate::LinkFallback;
+use markdown::mdast;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::Write;
+
+/// The destinations of the links that are written as numbered references, in the order
+/// they are first linked to.
+#[derive(Debug, Default)]
+pub struct LinkReferences(RefCell<Vec<Destination>>);
+
+/// The URL and the title of a link.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Destination {
+    pub url: String,
+    pub title: Option<String>,
+}
+
+/// The destinations of the definitions of a document, by their identifier. i.e `[id]: url`
+#[derive(Debug, Default)]
+pub struct LinkDefinitions(RefCell<HashMap<String, Destination>>);
+
+impl LinkDefinitions {
+    /// Adds the definitions in the nodes and all of their descendants. The first
+    /// definition of an identifier is kept, as it is the one that references use.
+    pub fn collect(&self, nodes: &[mdast::Node]) {
+        for node in nodes {
+            if let mdast::Node::Definition(definition) = node {
+                let destination = Destination {
+                    url: definition.url.clone(),
+                    title: definition.title.clone(),
+                };
+
+                (self.0.borrow_mut())
+                    .entry(definition.identifier.clone())
+                    .or_insert(destination);
+            }
+
+            if let Some(children) = node.children() {
+                self.collect(children);
+            }
+        }
+    }
+
+    /// Gets the destination of the definition with the identifier.
+    pub fn get(&self, identifier: &str) -> Option<Destination> {
+        self.0.borrow().get(identifier).cloned()
+    }
+}
+
+impl LinkReferences {
+    /// Gets the number of the destination, numbering it if it has not been linked to before.
+    fn number(&self, destination: Destination) -> usize {
+        let mut destinations = self.0.borrow_mut();
+        match destinations.iter().position(|known| *known == destination) {
+            Some(index) => index + 1,
+            None => {
+                destinations.push(destination);
+                destinations.len()
+            }
+        }
+    }
+}
+
+/// Writes the text of a link with the link theme.
+///
+/// The text is a hyperlink to the URL when the terminal supports them. Otherwise the URL
+/// and the title are written after the text, are numbered and written at the end of the
+/// document, or are left out, depending on the [`LinkFallback`].
+pub fn write_link(
+    link: &mdast::Link,
+    ctx: &Context,
+    writer: &mut impl Write,
+) -> Result<(), std::io::Error> {
+    let destination = Destination {
+        url: link.url.clone(),
+        title: link.title.clone(),
+    };
+
+    write_link_to(&link.children, destination, ctx, writer)
+}
+
+/// Writes a link that uses a definition in the same way as a link.
+pub fn write_link_reference(
+    reference: &mdast::LinkReference,
+    ctx: &Context,
+    writer: &mut impl Write,
+) -> Result<(), std::io::Error> {
+    // Markdown is only parsed as a reference when its definition exists, and the
+    // definitions are collected from the same markdown before it is written.
+    let Some(destination) = ctx.definitions.get(&reference.identifier) else {
+        return write_themed_text(ElementType::Nodes(&reference.children), ctx, None, writer);
+    };
+
+    write_link_to(&reference.children, destination, ctx, writer)
+}
+
+fn write_link_to(
+    children: &[mdast::Node],
+    destination: Destination,
+    ctx: &Context,
+    writer: &mut impl Write,
+) -> Result<(), std::io::Error> {
+    let is_hyperlink = ctx.is_writer_tty && ctx.hyperlinks;
+    if is_hyperlink {
+        write!(writer, "{T_ESC}]8;;{}{T_ESC}\\", destination.url)?;
+    }
+
+    write_themed_text(
+        ElementType::Nodes(children),
+        ctx,
+        Some(&ctx.theme.link),
+        writer,
+    )?;
+
+    if is_hyperlink {
+        return write!(writer, "{T_ESC}]8;;{T_ESC}\\");
+    }
+
+    // The URL of an autolink is already its text, so it is only written again when it
+    // has a title.
+    let is_autolink = is_autolink(children, &destination.url);
+    if is_autolink && destination.title.is_none() {
+        return Ok(());
+    }
+
+    match ctx.link_fallback {
+        LinkFallback::Inline if is_autolink => write!(writer, " ({})", title(&destination)),
+        LinkFallback::Inline => write!(writer, " ({})", destination),
+        LinkFallback::Numbered => write!(writer, "[{}]", ctx.links.number(destination)),
+        LinkFallback::TextOnly => Ok(()),
+    }
+}
+
+/// Writes the destinations of the numbered links that have been written, and forgets
+/// them. Returns whether anything was written.
+///
+/// `separator` is written before the destinations, to separate them from the document.
+pub fn write_link_references(
+    separator: &str,
+    ctx: &Context,
+    writer: &mut impl Write,
+) -> Result<bool, std::io::Error> {
+    let destinations = ctx.links.0.take();
+    if destinations.is_empty() {
+        return Ok(false);
+    }
+
+    write!(writer, "{separator}")?;
+    for (index, destination) in destinations.iter().enumerate() {
+        writeln!(writer, "[{}]: {destination}", index + 1)?;
+    }
+
+    Ok(true)
+}
+
+/// Indicates whether the text of the link is its URL. i.e `<https://a.com>`
+fn is_autolink(children: &[mdast::Node], url: &str) -> bool {
+    match children {
+        [mdast::Node::Text(text)] => {
+            text.value == url || url.strip_prefix("mailto:") == Some(&text.value)
+        }
+        _ => false,
+    }
+}
+
+/// Gets the title of the destination in quotes, or nothing when it has no title.
+fn title(destination: &Destination) -> String {
+    match &destination.title {
+        Some(title) => format!("\"{title}\""),
+        None => String::new(),
+    }
+}
+
+impl std::fmt::Display for Destination {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.title {
+            Some(_) => write!(f, "{} {}", self.url, title(self)),
+            None => write!(f, "{}", self.url),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::renderer::RenderOptions;
+    use crate::themes::get_dark_theme;
+    use crate::writer::{render_with_options, test_options};
+    use crate::{ElementTheme, LinkFallback, Theme};
+
+    fn render(input: &str, link_fallback: LinkFallback, is_writer_tty: bool) -> String {
+        let theme = Theme {
+            link: ElementTheme::default(),
+            ..get_dark_theme()
+        };
+        let options = RenderOptions {
+            link_fallback,
+            ..test_options()
+        };
+
+        render_with_options(input, &theme, &options, is_writer_tty)
+    }
+
+    #[test]
+    fn should_hyperlink_text_of_link_if_tty() {
+        let result = render(
+            "See [the **docs**](http://a.com).",
+            LinkFallback::Inline,
+            true,
+        );
+
+        assert_eq!(
+            result,
+            "See \u{1b}]8;;http://a.com\u{1b}\\the \u{1b}[1mdocs\u{1b}[0m\u{1b}]8;;\u{1b}\\."
+        );
+    }
+
+    #[test]
+    fn should_write_url_and_title_after_text() {
+        let input = "[a](http://a.com) and [b](http://b.com \"Bee\")";
+        let result = render(input, LinkFallback::Inline, false);
+
+        assert_eq!(result, "a (http://a.com) and b (http://b.com \"Bee\")");
+    }
+
+    #[test]
+    fn should_not_repeat_url_of_autolinks() {
+        let result = render("<http://a.com> and <me@a.com>", LinkFallback::Inline, false);
+
+        assert_eq!(result, "http://a.com and me@a.com");
+    }
+
+    #[test]
+    fn should_number_links_and_write_urls_at_end() {
+        let input = "[a](http://a.com), [b](http://b.com \"Bee\") and [a](http://a.com)\n\n- c";
+        let result = render(input, LinkFallback::Numbered, false);
+
+        assert_eq!(
+            result,
+            "a[1], b[2] and a[1]\n• c\n\n[1]: http://a.com\n[2]: http://b.com \"Bee\"\n"
+        );
+    }
+
+    #[test]
+    fn should_write_only_text_of_links() {
+        let result = render("[a](http://a.com \"A\")", LinkFallback::TextOnly, false);
+
+        assert_eq!(result, "a");
+    }
+
+    #[test]
+    fn should_resolve_references_to_definitions() {
+        let input = "[a][x], [X][] and [x]\n\n[x]: http://a.com \"A\"\n[x]: http://b.com";
+        let result = render(input, LinkFallback::Inline, false);
+
+        assert_eq!(
+            result,
+            "a (http://a.com \"A\"), X (http://a.com \"A\") and x (http://a.com \"A\")"
+        );
+    }
+
+    #[test]
+    fn should_resolve_image_references_to_definitions() {
+        let result = render("![alt][img]\n\n[img]: b.png", LinkFallback::Inline, false);
+
+        assert_eq!(result, "[image: alt](b.png)");
+    }
+}
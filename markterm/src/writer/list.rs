@@ -0,0 +1,298 @@
+use super::{ansi::visible_width, write_blocks, Context};
+use crate::{ElementTheme, NumberFormat, NumberStyle};
+use markdown::mdast;
+
+/// Writes a list with a bullet or a number in front of every item.
+///
+/// The markers of all the items are padded to the same width, so that numbers are
+/// right aligned, bullets are left aligned and the text of every item starts on the
+/// same column. The bullets of task list items are replaced with a checkbox.
+pub fn write_list(
+    list: &mdast::List,
+    ctx: &Context,
+    writer: &mut impl std::io::Write,
+) -> Result<(), std::io::Error> {
+    let ctx = Context {
+        list_depth: ctx.list_depth + 1,
+        ..*ctx
+    };
+
+    let items: Vec<&mdast::ListItem> = list
+        .children
+        .iter()
+        .filter_map(|child| match child {
+            mdast::Node::ListItem(item) => Some(item),
+            _ => None,
+        })
+        .collect();
+
+    let markers: Vec<String> = if list.ordered {
+        let start = list.start.unwrap_or(1);
+        let format = number_format(&ctx);
+        (0..items.len())
+            .map(|index| format.format(start.saturating_add(index as u32)))
+            .collect()
+    } else {
+        items
+            .iter()
+            .map(|item| match item.checked {
+                Some(checked) => checkbox(checked, &ctx).to_string(),
+                None => bullet(&ctx).to_string(),
+            })
+            .collect()
+    };
+
+    let marker_width = markers.iter().map(|m| visible_width(m)).max().unwrap_or(0);
+    let indent = ctx.theme.list_indent.max(marker_width + 1);
+
+    for (item, marker) in items.into_iter().zip(markers) {
+        // Numbers are kept for ordered task lists, so the checkbox is written with the text.
+        let checkbox = match (list.ordered, item.checked) {
+            (true, Some(checked)) => Some(checkbox(checked, &ctx)),
+            _ => None,
+        };
+
+        let item_marker = ItemMarker {
+            marker: &marker,
+            marker_width,
+            indent,
+            checkbox,
+            is_numbered: list.ordered,
+        };
+
+        write_list_item(item, &item_marker, &ctx, writer)?;
+    }
+
+    Ok(())
+}
+
+/// Describes what is written in front of the text of a list item.
+struct ItemMarker<'a> {
+    /// The bullet, number or checkbox.
+    marker: &'a str,
+
+    /// The width of the widest marker in the list.
+    marker_width: usize,
+
+    /// The column the text of the item starts on.
+    indent: usize,
+
+    /// A checkbox that is written in front of the text of the item.
+    checkbox: Option<&'a str>,
+
+    /// Indicates whether the marker is a number and should be right aligned.
+    is_numbered: bool,
+}
+
+fn write_list_item(
+    item: &mdast::ListItem,
+    item_marker: &ItemMarker,
+    ctx: &Context,
+    writer: &mut impl std::io::Write,
+) -> Result<(), std::io::Error> {
+    let item_theme = match item.checked {
+        Some(true) => &ctx.theme.task_checked,
+        Some(false) => &ctx.theme.task_unchecked,
+        None => &NO_THEME,
+    };
+
+    let mut write_intercept = Vec::new();
+    write_blocks(
+        &item.children,
+        &ctx.indented(item_marker.indent).styled(item_theme),
+        &mut write_intercept,
+    )?;
+    let text = String::from_utf8_lossy(&write_intercept);
+
+    let ItemMarker {
+        marker,
+        marker_width,
+        indent,
+        checkbox,
+        is_numbered,
+    } = item_marker;
+
+    let space = marker_width - visible_width(marker);
+    let (alignment, padding) = match is_numbered {
+        true => (" ".repeat(space), " ".repeat(indent - marker_width)),
+        false => (String::new(), " ".repeat(indent - marker_width + space)),
+    };
+    let continuation = " ".repeat(*indent);
+
+    writeln!(writer)?;
+    write!(writer, "{alignment}")?;
+    let marker_theme = match (checkbox, item.checked) {
+        (None, Some(_)) => item_theme,
+        _ => &ctx.theme.list,
+    };
+    marker_theme.write_with_depth(|w| write!(w, "{marker}"), writer, ctx.colors())?;
+    write!(writer, "{padding}")?;
+
+    if let Some(checkbox) = checkbox {
+        item_theme.write_with_depth(|w| write!(w, "{checkbox}"), writer, ctx.colors())?;
+        write!(writer, " ")?;
+    }
+
+    // The item theme is applied line by line so that the indentation is not styled.
+    let mut lines = text.lines();
+    let first_line = lines.next().unwrap_or("");
+    item_theme.write_with_depth(|w| write!(w, "{first_line}"), writer, ctx.colors())?;
+    writeln!(writer)?;
+
+    for line in lines {
+        if !line.is_empty() {
+            write!(writer, "{continuation}")?;
+            item_theme.write_with_depth(|w| write!(w, "{line}"), writer, ctx.colors())?;
+        }
+
+        writeln!(writer)?;
+    }
+
+    Ok(())
+}
+
+const NO_THEME: ElementTheme = ElementTheme {
+    fg: None,
+    bg: None,
+    style: crate::TextStyle::Normal,
+};
+
+/// Gets the checkbox for a task list item. Output that is not Unicode uses `[x]` and `[ ]`.
+fn checkbox(checked: bool, ctx: &Context) -> &'static str {
+    match (checked, ctx.unicode) {
+        (true, true) => "☑",
+        (false, true) => "☐",
+        (true, false) => "[x]",
+        (false, false) => "[ ]",
+    }
+}
+
+/// Gets the bullet for the depth of the list. Deeper lists wrap around to the first bullet.
+fn bullet(ctx: &Context) -> char {
+    let bullets = &ctx.theme.list_bullets;
+    match bullets.len() {
+        0 => '•',
+        len => bullets[(ctx.list_depth - 1) % len],
+    }
+}
+
+/// Gets the number format for the depth of the list. Deeper lists wrap around to the first format.
+fn number_format(ctx: &Context) -> NumberFormat {
+    let formats = &ctx.theme.list_number_formats;
+    match formats.len() {
+        0 => NumberFormat::new(NumberStyle::Decimal, "."),
+        len => formats[(ctx.list_depth - 1) % len].clone(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::renderer::RenderOptions;
+    use crate::themes::get_dark_theme;
+    use crate::writer::{render_with_options, test_options, write};
+    use crate::{NumberFormat, NumberStyle, Theme};
+
+    use colored::Colorize;
+
+    fn render(input: &str, theme: &Theme) -> String {
+        let mut result = Vec::new();
+        write(input, theme, &mut result, false).unwrap();
+
+        String::from_utf8(result).unwrap()
+    }
+
+    #[test]
+    fn should_write_checkboxes_for_tasks_if_not_unicode() {
+        let input = r#"- [x] Done
+- [ ] Todo
+- Plain"#;
+        let options = RenderOptions {
+            unicode: false,
+            ..test_options()
+        };
+
+        let expected = "\n[x] Done\n\n[ ] Todo\n\n•   Plain\n";
+        assert_eq!(
+            render_with_options(input, &get_dark_theme(), &options, false),
+            expected
+        );
+    }
+
+    #[test]
+    fn should_write_checkbox_glyphs_and_task_themes() {
+        let theme = Theme {
+            task_checked: crate::ElementTheme::new(None, None, crate::TextStyle::Strikethrough),
+            ..get_dark_theme()
+        };
+        let input = r#"- [x] Done
+- [ ] Todo"#;
+
+        let mut result = Vec::new();
+        write(input, &theme, &mut result, true).unwrap();
+        let result = String::from_utf8(result).unwrap();
+
+        let expected = format!(
+            "\n{} {}\n\n☐ Todo\n",
+            "☑".strikethrough(),
+            "Done".strikethrough()
+        );
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn should_keep_numbers_for_ordered_tasks() {
+        let input = r#"1. [x] Done
+2. [ ] Todo"#;
+
+        let expected = "\n1. ☑ Done\n\n2. ☐ Todo\n";
+        assert_eq!(render(input, &get_dark_theme()), expected);
+    }
+
+    #[test]
+    fn should_number_ordered_lists() {
+        let input = r#"1. One
+2. Two"#;
+
+        let expected = "\n1. One\n\n2. Two\n";
+        assert_eq!(render(input, &get_dark_theme()), expected);
+    }
+
+    #[test]
+    fn should_start_numbering_from_start() {
+        let input = r#"3. Three
+4. Four"#;
+
+        let expected = "\n3. Three\n\n4. Four\n";
+        assert_eq!(render(input, &get_dark_theme()), expected);
+    }
+
+    #[test]
+    fn should_right_align_numbers() {
+        let input = r#"9. Nine
+10. Ten
+    continued"#;
+
+        let expected = "\n 9. Nine\n\n10. Ten\n    continued\n";
+        assert_eq!(render(input, &get_dark_theme()), expected);
+    }
+
+    #[test]
+    fn should_use_number_formats_from_theme_per_depth() {
+        let theme = Theme {
+            list_number_formats: vec![
+                NumberFormat::new(NumberStyle::Decimal, ")"),
+                NumberFormat::new(NumberStyle::UpperRoman, "."),
+            ],
+            ..get_dark_theme()
+        };
+        let input = r#"1. One
+   1. Nested
+   2. Nested
+   3. Nested
+   4. Nested"#;
+
+        let expected =
+            "\n1) One\n     I. Nested\n\n    II. Nested\n\n   III. Nested\n\n    IV. Nested\n";
+        assert_eq!(render(input, &theme), expected);
+    }
+}
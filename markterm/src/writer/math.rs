@@ -0,0 +1,408 @@
+use super::script::{subscript, superscript};
+use super::{write_themed_text, Context, ElementType};
+use markdown::mdast;
+use std::io::Write;
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// Writes inline math with Unicode characters in place of its TeX, or as the TeX when it
+/// cannot be converted.
+pub fn write_inline_math(
+    math: &mdast::InlineMath,
+    ctx: &Context,
+    writer: &mut impl Write,
+) -> Result<(), std::io::Error> {
+    let text = convert(&math.value, ctx);
+    write_themed_text(ElementType::Text(&text), ctx, Some(&ctx.theme.math), writer)
+}
+
+/// Writes a block of math on its own lines, indented in the same way as the lines of a
+/// code block are.
+pub fn write_math(
+    math: &mdast::Math,
+    ctx: &Context,
+    writer: &mut impl Write,
+) -> Result<(), std::io::Error> {
+    writeln!(writer)?;
+    for line in convert(&math.value, ctx).lines().map(str::trim) {
+        write!(writer, "  ")?;
+        write_themed_text(ElementType::Text(line), ctx, Some(&ctx.theme.math), writer)?;
+        writeln!(writer)?;
+    }
+
+    Ok(())
+}
+
+/// Converts the TeX to Unicode, or keeps it as it is if the output is not Unicode or
+/// any part of it cannot be converted.
+fn convert(tex: &str, ctx: &Context) -> String {
+    let converted = match ctx.unicode {
+        true => Converter::new(tex).sequence(false),
+        false => None,
+    };
+
+    converted.unwrap_or_else(|| tex.trim().to_string())
+}
+
+/// Converts TeX to Unicode one construct at a time.
+struct Converter<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl<'a> Converter<'a> {
+    fn new(tex: &'a str) -> Self {
+        Converter {
+            chars: tex.trim().chars().peekable(),
+        }
+    }
+
+    /// Converts everything up to the end of the TeX, or up to the brace that closes the
+    /// group when the TeX is in a group.
+    fn sequence(&mut self, is_group: bool) -> Option<String> {
+        let mut result = String::new();
+        while let Some(c) = self.chars.next() {
+            match c {
+                '}' if is_group => return Some(result),
+                '}' => return None,
+                '{' => result.push_str(&self.sequence(true)?),
+                '^' => result.push_str(&superscript(&self.argument()?)?),
+                '_' => result.push_str(&subscript(&self.argument()?)?),
+                '\\' => result.push_str(&self.command()?),
+                '~' | '\n' => result.push(' '),
+                '\'' => result.push('′'),
+                '-' => result.push('−'),
+                '*' => result.push('∗'),
+                '&' => {}
+                c => result.push(c),
+            }
+        }
+
+        (!is_group).then_some(result)
+    }
+
+    /// Converts the argument of a command or a script, which is a group, a command or a
+    /// single character.
+    fn argument(&mut self) -> Option<String> {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+
+        match self.chars.next()? {
+            '{' => self.sequence(true),
+            '\\' => self.command(),
+            c => Some(c.to_string()),
+        }
+    }
+
+    /// Converts the command after a `\`.
+    fn command(&mut self) -> Option<String> {
+        let mut name = String::new();
+        while let Some(c) = self.chars.next_if(|c| c.is_ascii_alphabetic()) {
+            name.push(c);
+        }
+
+        if name.is_empty() {
+            return match self.chars.next()? {
+                '\\' => Some("\n".to_string()),
+                ',' | ':' | ';' | ' ' => Some(" ".to_string()),
+                '!' => Some(String::new()),
+                c @ ('{' | '}' | '%' | '$' | '&' | '#' | '_' | '|') => Some(c.to_string()),
+                _ => None,
+            };
+        }
+
+        match name.as_str() {
+            "frac" | "dfrac" | "tfrac" => {
+                let numerator = self.argument()?;
+                let denominator = self.argument()?;
+                Some(format!("{}⁄{}", grouped(numerator), grouped(denominator)))
+            }
+            "sqrt" => {
+                let root = match self.chars.next_if_eq(&'[') {
+                    Some(_) => match self.sequence_until(']')?.trim() {
+                        "3" => '∛',
+                        "4" => '∜',
+                        _ => return None,
+                    },
+                    None => '√',
+                };
+
+                Some(format!("{root}{}", grouped(self.argument()?)))
+            }
+            "text" | "textrm" | "textit" | "textbf" | "mbox" => self.text_argument(),
+            "mathrm" | "mathit" | "mathbf" | "operatorname" => self.argument(),
+            "mathbb" => (self.argument()?.chars())
+                .map(|c| match c {
+                    'C' => Some('ℂ'),
+                    'N' => Some('ℕ'),
+                    'P' => Some('ℙ'),
+                    'Q' => Some('ℚ'),
+                    'R' => Some('ℝ'),
+                    'Z' => Some('ℤ'),
+                    _ => None,
+                })
+                .collect(),
+            "left" | "right" | "big" | "Big" | "bigg" | "Bigg" => {
+                // `\left.` is an empty delimiter.
+                self.chars.next_if_eq(&'.');
+                Some(String::new())
+            }
+            "displaystyle" | "textstyle" | "limits" | "nolimits" => Some(String::new()),
+            "quad" => Some("  ".to_string()),
+            "qquad" => Some("    ".to_string()),
+            "sin" | "cos" | "tan" | "cot" | "sec" | "csc" | "arcsin" | "arccos" | "arctan"
+            | "sinh" | "cosh" | "tanh" | "log" | "ln" | "lg" | "exp" | "lim" | "min" | "max"
+            | "sup" | "inf" | "det" | "dim" | "gcd" | "deg" | "arg" | "Pr" | "mod" => Some(name),
+            _ => symbol(&name).map(String::from),
+        }
+    }
+
+    /// Gets the argument of a command that switches to text, such as `\text`, whose
+    /// characters are kept as they are written.
+    fn text_argument(&mut self) -> Option<String> {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+
+        if self.chars.next_if_eq(&'{').is_none() {
+            return self.chars.next().map(String::from);
+        }
+
+        let mut text = String::new();
+        let mut depth = 0;
+        loop {
+            match self.chars.next()? {
+                '}' if depth == 0 => return Some(text),
+                c => {
+                    match c {
+                        '{' => depth += 1,
+                        '}' => depth -= 1,
+                        _ => {}
+                    }
+
+                    text.push(c);
+                }
+            }
+        }
+    }
+
+    /// Converts everything up to the character, such as the index of a root.
+    fn sequence_until(&mut self, end: char) -> Option<String> {
+        let mut text = String::new();
+        loop {
+            match self.chars.next()? {
+                c if c == end => return Some(text),
+                c => text.push(c),
+            }
+        }
+    }
+}
+
+/// Puts the text in parentheses when it is more than a single number or name, so that
+/// it reads the same after a fraction slash or a root.
+fn grouped(text: String) -> String {
+    match text.chars().all(char::is_alphanumeric) {
+        true => text,
+        false => format!("({text})"),
+    }
+}
+
+/// Gets the character of a command that is a symbol, such as a Greek letter or an operator.
+fn symbol(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "alpha" => "α",
+        "beta" => "β",
+        "gamma" => "γ",
+        "delta" => "δ",
+        "epsilon" => "ϵ",
+        "varepsilon" => "ε",
+        "zeta" => "ζ",
+        "eta" => "η",
+        "theta" => "θ",
+        "vartheta" => "ϑ",
+        "iota" => "ι",
+        "kappa" => "κ",
+        "lambda" => "λ",
+        "mu" => "μ",
+        "nu" => "ν",
+        "xi" => "ξ",
+        "omicron" => "ο",
+        "pi" => "π",
+        "varpi" => "ϖ",
+        "rho" => "ρ",
+        "varrho" => "ϱ",
+        "sigma" => "σ",
+        "varsigma" => "ς",
+        "tau" => "τ",
+        "upsilon" => "υ",
+        "phi" => "ϕ",
+        "varphi" => "φ",
+        "chi" => "χ",
+        "psi" => "ψ",
+        "omega" => "ω",
+        "Gamma" => "Γ",
+        "Delta" => "Δ",
+        "Theta" => "Θ",
+        "Lambda" => "Λ",
+        "Xi" => "Ξ",
+        "Pi" => "Π",
+        "Sigma" => "Σ",
+        "Upsilon" => "Υ",
+        "Phi" => "Φ",
+        "Psi" => "Ψ",
+        "Omega" => "Ω",
+        "sum" => "∑",
+        "prod" => "∏",
+        "coprod" => "∐",
+        "int" => "∫",
+        "iint" => "∬",
+        "iiint" => "∭",
+        "oint" => "∮",
+        "infty" => "∞",
+        "partial" => "∂",
+        "nabla" => "∇",
+        "pm" => "±",
+        "mp" => "∓",
+        "times" => "×",
+        "div" => "÷",
+        "cdot" => "⋅",
+        "ast" => "∗",
+        "star" => "⋆",
+        "circ" => "∘",
+        "bullet" => "∙",
+        "oplus" => "⊕",
+        "otimes" => "⊗",
+        "le" | "leq" => "≤",
+        "ge" | "geq" => "≥",
+        "ll" => "≪",
+        "gg" => "≫",
+        "ne" | "neq" => "≠",
+        "approx" => "≈",
+        "equiv" => "≡",
+        "sim" => "∼",
+        "simeq" => "≃",
+        "cong" => "≅",
+        "propto" => "∝",
+        "to" | "rightarrow" => "→",
+        "gets" | "leftarrow" => "←",
+        "leftrightarrow" => "↔",
+        "Rightarrow" => "⇒",
+        "Leftarrow" => "⇐",
+        "Leftrightarrow" => "⇔",
+        "implies" => "⟹",
+        "iff" => "⟺",
+        "mapsto" => "↦",
+        "uparrow" => "↑",
+        "downarrow" => "↓",
+        "in" => "∈",
+        "notin" => "∉",
+        "ni" => "∋",
+        "subset" => "⊂",
+        "subseteq" => "⊆",
+        "supset" => "⊃",
+        "supseteq" => "⊇",
+        "cup" => "∪",
+        "cap" => "∩",
+        "setminus" => "∖",
+        "emptyset" | "varnothing" => "∅",
+        "forall" => "∀",
+        "exists" => "∃",
+        "nexists" => "∄",
+        "neg" | "lnot" => "¬",
+        "land" | "wedge" => "∧",
+        "lor" | "vee" => "∨",
+        "angle" => "∠",
+        "perp" => "⊥",
+        "parallel" => "∥",
+        "mid" => "∣",
+        "ldots" | "dots" => "…",
+        "cdots" => "⋯",
+        "vdots" => "⋮",
+        "ddots" => "⋱",
+        "prime" => "′",
+        "hbar" => "ℏ",
+        "ell" => "ℓ",
+        "Re" => "ℜ",
+        "Im" => "ℑ",
+        "aleph" => "ℵ",
+        "degree" => "°",
+        "langle" => "⟨",
+        "rangle" => "⟩",
+        "lfloor" => "⌊",
+        "rfloor" => "⌋",
+        "lceil" => "⌈",
+        "rceil" => "⌉",
+        "vert" => "|",
+        "Vert" => "‖",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::Converter;
+    use crate::renderer::RenderOptions;
+    use crate::themes::get_dark_theme;
+    use crate::writer::{render_with_options, test_options};
+
+    fn convert(tex: &str) -> Option<String> {
+        Converter::new(tex).sequence(false)
+    }
+
+    fn render(input: &str, unicode: bool) -> String {
+        let options = RenderOptions {
+            unicode,
+            ..test_options()
+        };
+
+        render_with_options(input, &get_dark_theme(), &options, false)
+    }
+
+    macro_rules! conversions {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (tex, expected) = $value;
+                assert_eq!(convert(tex).as_deref(), expected);
+            }
+        )*
+        }
+    }
+
+    conversions! {
+        should_convert_greek_letters: (r"\alpha + \Omega", Some("α + Ω")),
+        should_convert_scripts: (r"x^2 + a_{ij} - e^{-x}", Some("x² + aᵢⱼ − e⁻ˣ")),
+        should_convert_operators: (r"\sum_{i=1}^n \int_0^1 f \leq \pi", Some("∑ᵢ₌₁ⁿ ∫₀¹ f ≤ π")),
+        should_convert_fractions: (r"\frac{a}{b} + \frac{x+1}{2}", Some("a⁄b + (x+1)⁄2")),
+        should_convert_roots: (r"\sqrt{2} \sqrt[3]{x+y}", Some("√2 ∛(x+y)")),
+        should_keep_text_as_it_is: (r"x \text{ is well-known}", Some("x  is well-known")),
+        should_convert_blackboard_letters: (r"x \in \mathbb{R}", Some("x ∈ ℝ")),
+        should_not_convert_unknown_commands: (r"\begin{matrix} a \end{matrix}", None),
+        should_not_convert_scripts_without_characters: (r"x^{Q}", None),
+    }
+
+    #[test]
+    fn should_write_inline_math() {
+        let result = render("Energy $E = mc^2$ here", true);
+
+        assert_eq!(result, "Energy E = mc² here");
+    }
+
+    #[test]
+    fn should_write_tex_if_not_converted() {
+        let result = render(r"Matrix $\begin{pmatrix}a\end{pmatrix}$", true);
+
+        assert_eq!(result, r"Matrix \begin{pmatrix}a\end{pmatrix}");
+    }
+
+    #[test]
+    fn should_write_math_blocks_on_own_lines() {
+        let result = render("Where\n\n$$\n\\sqrt{x} \\\\\n\\alpha\n$$\n\nDone", true);
+
+        assert_eq!(result, "Where\n\n  √x\n  α\nDone");
+    }
+
+    #[test]
+    fn should_write_tex_if_not_unicode() {
+        let result = render(r"$\alpha$", false);
+
+        assert_eq!(result, r"\alpha");
+    }
+}
@@ -0,0 +1,407 @@
+use super::{ansi::visible_width, write_blocks, Context};
+use crate::{ElementTheme, Theme};
+use markdown::mdast;
+use std::io::Write;
+
+/// The number of columns taken by the bar in front of the lines of a block quote,
+/// including the space after it.
+const BAR_WIDTH: usize = 2;
+
+/// The number of columns taken by the borders of an alert box and the spaces inside them.
+const BOX_WIDTH: usize = 4;
+
+/// Writes a block quote with a bar in front of every line.
+///
+/// The lines are prefixed as they are written, so the quote is not buffered. Nested
+/// quotes use the bar and the theme of their depth. Quotes that start with an alert
+/// marker such as `[!NOTE]` are written as a box instead.
+pub fn write_blockquote(
+    block_quote: &mdast::Blockquote,
+    ctx: &Context,
+    writer: &mut impl Write,
+) -> Result<(), std::io::Error> {
+    if let Some((kind, children)) = split_alert(&block_quote.children) {
+        return write_alert(kind, &children, ctx, writer);
+    }
+
+    let ctx = Context {
+        quote_depth: ctx.quote_depth + 1,
+        ..ctx.indented(BAR_WIDTH)
+    };
+
+    let mut prefix = Vec::new();
+    bar_theme(&ctx).write_with_depth(|w| write!(w, "{}", bar(&ctx)), &mut prefix, ctx.colors())?;
+    prefix.push(b' ');
+
+    let mut writer = LinePrefixer {
+        inner: writer,
+        prefix: &prefix,
+        is_line_start: true,
+    };
+
+    write_blocks(&block_quote.children, &ctx, &mut writer)?;
+
+    // The last line of the quote is always ended, so that the bar does not run into
+    // whatever is written after it.
+    if !writer.is_line_start {
+        writeln!(writer.inner)?;
+    }
+
+    Ok(())
+}
+
+/// Writes an alert as a box with the icon and the title of the alert in its top border.
+///
+/// The content is written up front, so that the box can be made as wide as its longest
+/// line when text is not wrapped. The border uses the color of the alert and the title
+/// uses its whole theme. The box is drawn with ASCII and the icon is left out when the
+/// output is not Unicode.
+fn write_alert<W: Write>(
+    kind: AlertKind,
+    children: &[mdast::Node],
+    ctx: &Context,
+    writer: &mut W,
+) -> Result<(), std::io::Error> {
+    let ctx = Context {
+        quote_depth: ctx.quote_depth + 1,
+        ..ctx.indented(BOX_WIDTH)
+    };
+
+    let mut content = Vec::new();
+    write_blocks(children, &ctx, &mut content)?;
+    let content = String::from_utf8_lossy(&content);
+    let lines: Vec<&str> = content.trim_end_matches('\n').split('\n').collect();
+
+    let title = match ctx.unicode {
+        true => format!("{} {}", kind.icon(), kind.title()),
+        false => kind.title().to_string(),
+    };
+    let title_width = visible_width(&title);
+    let inner_width = lines
+        .iter()
+        .map(|line| visible_width(line))
+        .chain(std::iter::once(title_width + 2))
+        .chain(ctx.width)
+        .max()
+        .unwrap_or(0);
+
+    let (corners, horizontal, vertical) = match ctx.unicode {
+        true => (['┌', '┐', '└', '┘'], '─', '│'),
+        false => (['+', '+', '+', '+'], '-', '|'),
+    };
+
+    // Only the color of the alert is used, so that the borders are not bold.
+    let border_theme = ElementTheme {
+        fg: kind.theme(ctx.theme).fg,
+        ..Default::default()
+    };
+    let border = |text: &str, writer: &mut W| {
+        border_theme.write_with_depth(|w| write!(w, "{text}"), writer, ctx.colors())
+    };
+
+    border(&format!("{}{horizontal} ", corners[0]), writer)?;
+    kind.theme(ctx.theme)
+        .write_with_depth(|w| write!(w, "{title}"), writer, ctx.colors())?;
+    let fill = horizontal.to_string().repeat(inner_width - title_width - 1);
+    border(&format!(" {fill}{}", corners[1]), writer)?;
+    writeln!(writer)?;
+
+    for line in lines {
+        border(&vertical.to_string(), writer)?;
+        let padding = " ".repeat(inner_width - visible_width(line));
+        write!(writer, " {line}{padding} ")?;
+        border(&vertical.to_string(), writer)?;
+        writeln!(writer)?;
+    }
+
+    let bottom = horizontal.to_string().repeat(inner_width + 2);
+    border(&format!("{}{bottom}{}", corners[2], corners[3]), writer)?;
+    writeln!(writer)
+}
+
+/// Gets the bar for the depth of the quote. Deeper quotes wrap around to the first bar.
+fn bar(ctx: &Context) -> char {
+    let bars = &ctx.theme.indent_bars;
+    match bars.len() {
+        0 => '│',
+        len => bars[(ctx.quote_depth - 1) % len],
+    }
+}
+
+/// Gets the theme of the bar for the depth of the quote, which is the theme of the
+/// level combined with the theme of all indents.
+fn bar_theme(ctx: &Context) -> ElementTheme {
+    let levels = &ctx.theme.indent_levels;
+    match levels.len() {
+        0 => ctx.theme.indents,
+        len => ctx
+            .theme
+            .indents
+            .combine(&levels[(ctx.quote_depth - 1) % len]),
+    }
+}
+
+/// The kinds of alerts that GitHub renders for block quotes that start with `[!KIND]`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum AlertKind {
+    Note,
+    Tip,
+    Important,
+    Warning,
+    Caution,
+}
+
+impl AlertKind {
+    fn from_name(name: &str) -> Option<Self> {
+        [
+            AlertKind::Note,
+            AlertKind::Tip,
+            AlertKind::Important,
+            AlertKind::Warning,
+            AlertKind::Caution,
+        ]
+        .into_iter()
+        .find(|kind| kind.title().eq_ignore_ascii_case(name))
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            AlertKind::Note => "Note",
+            AlertKind::Tip => "Tip",
+            AlertKind::Important => "Important",
+            AlertKind::Warning => "Warning",
+            AlertKind::Caution => "Caution",
+        }
+    }
+
+    fn icon(self) -> char {
+        match self {
+            AlertKind::Note => 'ⓘ',
+            AlertKind::Tip => '★',
+            AlertKind::Important => '‼',
+            AlertKind::Warning => '⚠',
+            AlertKind::Caution => '✖',
+        }
+    }
+
+    fn theme(self, theme: &Theme) -> &ElementTheme {
+        match self {
+            AlertKind::Note => &theme.alert_note,
+            AlertKind::Tip => &theme.alert_tip,
+            AlertKind::Important => &theme.alert_important,
+            AlertKind::Warning => &theme.alert_warning,
+            AlertKind::Caution => &theme.alert_caution,
+        }
+    }
+}
+
+/// Gets the kind of alert of a block quote and its content without the marker, when its
+/// first line is only an alert marker like `[!NOTE]`.
+fn split_alert(children: &[mdast::Node]) -> Option<(AlertKind, Vec<mdast::Node>)> {
+    let (mdast::Node::Paragraph(paragraph), rest) = children.split_first()? else {
+        return None;
+    };
+    let Some(mdast::Node::Text(text)) = paragraph.children.first() else {
+        return None;
+    };
+
+    let (name, after) = text.value.strip_prefix("[!")?.split_once(']')?;
+    let kind = AlertKind::from_name(name)?;
+    let (line, body) = after.split_once('\n').unwrap_or((after, ""));
+    if !line.trim().is_empty() {
+        return None;
+    }
+
+    let mut paragraph = paragraph.clone();
+    match body.is_empty() {
+        true => {
+            paragraph.children.remove(0);
+        }
+        false => {
+            paragraph.children[0] = mdast::Node::Text(mdast::Text {
+                value: body.to_string(),
+                position: None,
+            });
+        }
+    }
+
+    let mut children = Vec::with_capacity(children.len());
+    if !paragraph.children.is_empty() {
+        children.push(mdast::Node::Paragraph(paragraph));
+    }
+
+    children.extend_from_slice(rest);
+    Some((kind, children))
+}
+
+/// Writes the prefix in front of every line that is written to the inner writer.
+///
+/// The inner writer is a trait object, so that nested quotes do not create a new type
+/// of writer for every level.
+pub(super) struct LinePrefixer<'a> {
+    pub inner: &'a mut dyn Write,
+    pub prefix: &'a [u8],
+
+    /// Indicates whether the next byte starts a line.
+    pub is_line_start: bool,
+}
+
+impl Write for LinePrefixer<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let mut rest = buf;
+        while !rest.is_empty() {
+            if self.is_line_start {
+                self.inner.write_all(self.prefix)?;
+                self.is_line_start = false;
+            }
+
+            match rest.iter().position(|byte| *byte == b'\n') {
+                Some(index) => {
+                    self.inner.write_all(&rest[..=index])?;
+                    self.is_line_start = true;
+                    rest = &rest[index + 1..];
+                }
+                None => {
+                    self.inner.write_all(rest)?;
+                    rest = &[];
+                }
+            }
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::renderer::RenderOptions;
+    use crate::themes::get_dark_theme;
+    use crate::writer::{render_with_options, test_options};
+    use crate::{ElementTheme, TextStyle, Theme};
+
+    fn render_to_width(input: &str, width: Option<usize>, is_writer_tty: bool) -> String {
+        let options = RenderOptions {
+            width,
+            ..test_options()
+        };
+
+        render_with_options(input, &theme(), &options, is_writer_tty)
+    }
+
+    fn render(input: &str, is_writer_tty: bool) -> String {
+        render_to_width(input, None, is_writer_tty)
+    }
+
+    fn theme() -> Theme {
+        Theme {
+            indents: ElementTheme::new(Some("#555"), None, TextStyle::Normal),
+            indent_bars: vec!['│', '┆'],
+            indent_levels: vec![
+                ElementTheme::default(),
+                ElementTheme::new(Some("#F00"), None, TextStyle::Bold),
+            ],
+            ..get_dark_theme()
+        }
+    }
+
+    #[test]
+    fn should_theme_bar_if_tty() {
+        let result = render("> quote", true);
+
+        assert_eq!(result, "\u{1b}[38;2;85;85;85m│\u{1b}[0m quote\n");
+    }
+
+    #[test]
+    fn should_use_bar_and_theme_of_each_depth() {
+        let result = render("> one\n>\n> > two", true);
+        let outer = "\u{1b}[38;2;85;85;85m│\u{1b}[0m ";
+        let inner = "\u{1b}[1;38;2;255;0;0m┆\u{1b}[0m ";
+
+        assert_eq!(result, format!("{outer}one\n{outer}\n{outer}{inner}two\n"));
+    }
+
+    #[test]
+    fn should_keep_bar_on_blank_lines_if_not_tty() {
+        let result = render("> one\n>\n> two", false);
+
+        assert_eq!(result, "│ one\n│ \n│ two\n");
+    }
+
+    #[test]
+    fn should_keep_bar_on_wrapped_lines() {
+        let result = render_to_width("> > one two three", Some(10), false);
+
+        assert_eq!(result, "│ ┆ one\n│ ┆ two\n│ ┆ three\n");
+    }
+
+    #[test]
+    fn should_write_title_of_alert() {
+        let result = render("> [!note]\n> Read *this*.", false);
+
+        assert_eq!(result, "┌─ ⓘ Note ───┐\n│ Read this. │\n└────────────┘\n");
+    }
+
+    #[test]
+    fn should_drop_paragraph_of_alert_marker() {
+        let result = render("> [!WARNING]\n>\n> Careful", false);
+
+        assert_eq!(
+            result,
+            "┌─ ⚠ Warning ─┐\n│ Careful     │\n└─────────────┘\n"
+        );
+    }
+
+    #[test]
+    fn should_theme_title_and_bar_of_alert() {
+        let theme = Theme {
+            alert_tip: ElementTheme::new(Some("#0F0"), None, TextStyle::Bold),
+            ..theme()
+        };
+        let result = render_with_options("> [!TIP]\n> Tip", &theme, &test_options(), true);
+        let border = |text| format!("\u{1b}[38;2;0;255;0m{text}\u{1b}[0m");
+
+        assert_eq!(
+            result,
+            format!(
+                "{}\u{1b}[1;38;2;0;255;0m★ Tip\u{1b}[0m{}\n{} Tip     {}\n{}\n",
+                border("┌─ "),
+                border(" ─┐"),
+                border("│"),
+                border("│"),
+                border("└─────────┘")
+            )
+        );
+    }
+
+    #[test]
+    fn should_fill_wrap_width_with_alert() {
+        let result = render_to_width("> [!NOTE]\n> one two three", Some(14), false);
+
+        assert_eq!(
+            result,
+            "┌─ ⓘ Note ───┐\n│ one two    │\n│ three      │\n└────────────┘\n"
+        );
+    }
+
+    #[test]
+    fn should_draw_alert_with_ascii_if_not_unicode() {
+        let options = RenderOptions {
+            unicode: false,
+            ..test_options()
+        };
+        let result = render_with_options("> [!CAUTION]\n> Hot", &theme(), &options, false);
+
+        assert_eq!(result, "+- Caution -+\n| Hot       |\n+-----------+\n");
+    }
+
+    #[test]
+    fn should_not_write_alert_if_marker_is_followed_by_text() {
+        let result = render("> [!CAUTION] text", false);
+
+        assert_eq!(result, "│ [!CAUTION] text\n");
+    }
+}
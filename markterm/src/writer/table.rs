@@ -0,0 +1,196 @@
+use super::{ansi::visible_width, write_raw_text, Context};
+use markdown::mdast::{self, AlignKind};
+
+/// Writes a GFM table to the writer with box drawing borders.
+///
+/// Every cell is rendered up front so that the width of each column is known
+/// before the first border is drawn.
+pub fn write_table(
+    table: &mdast::Table,
+    ctx: &Context,
+    writer: &mut impl std::io::Write,
+) -> Result<(), std::io::Error> {
+    let theme = ctx.theme;
+    let colors = ctx.colors();
+    let rows = render_cells(table, ctx)?;
+
+    let column_count = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let mut widths = vec![0; column_count];
+    for row in &rows {
+        for (index, cell) in row.iter().enumerate() {
+            widths[index] = widths[index].max(visible_width(cell));
+        }
+    }
+
+    writeln!(writer)?;
+    write_border(&widths, ('┌', '┬', '┐'), ctx, writer)?;
+
+    for (index, row) in rows.iter().enumerate() {
+        if index == 1 {
+            write_border(&widths, ('├', '┼', '┤'), ctx, writer)?;
+        }
+
+        theme
+            .table_border
+            .write_with_depth(|w| write!(w, "│"), writer, colors)?;
+
+        for (column, width) in widths.iter().enumerate() {
+            let cell = row.get(column).map(String::as_str).unwrap_or("");
+            let align = table.align.get(column).unwrap_or(&AlignKind::None);
+            let (left, right) = padding(align, width - visible_width(cell));
+
+            write!(writer, " {}", " ".repeat(left))?;
+            if index == 0 {
+                theme
+                    .table_header
+                    .write_with_depth(|w| write!(w, "{cell}"), writer, colors)?;
+            } else {
+                write!(writer, "{cell}")?;
+            }
+            write!(writer, "{} ", " ".repeat(right))?;
+
+            theme
+                .table_border
+                .write_with_depth(|w| write!(w, "│"), writer, colors)?;
+        }
+
+        writeln!(writer)?;
+    }
+
+    write_border(&widths, ('└', '┴', '┘'), ctx, writer)
+}
+
+/// Renders the inline contents of every cell, keeping any formatting within them.
+fn render_cells(table: &mdast::Table, ctx: &Context) -> Result<Vec<Vec<String>>, std::io::Error> {
+    let mut rows = Vec::new();
+
+    for (index, row) in table.children.iter().enumerate() {
+        let mdast::Node::TableRow(row) = row else {
+            continue;
+        };
+
+        // The cells of the header row are written inside the header theme.
+        let row_ctx = match index {
+            0 => ctx.styled(&ctx.theme.table_header),
+            _ => *ctx,
+        };
+
+        let mut cells = Vec::new();
+        for cell in &row.children {
+            let mut write_intercept = Vec::new();
+            if let mdast::Node::TableCell(cell) = cell {
+                write_raw_text(&cell.children, &row_ctx, &mut write_intercept)?;
+            }
+
+            cells.push(String::from_utf8_lossy(&write_intercept).into_owned());
+        }
+
+        rows.push(cells);
+    }
+
+    Ok(rows)
+}
+
+/// Splits the free space in a cell into the padding before and after the text.
+fn padding(align: &AlignKind, space: usize) -> (usize, usize) {
+    match align {
+        AlignKind::Right => (space, 0),
+        AlignKind::Center => (space / 2, space - space / 2),
+        AlignKind::Left | AlignKind::None => (0, space),
+    }
+}
+
+fn write_border(
+    widths: &[usize],
+    (start, middle, end): (char, char, char),
+    ctx: &Context,
+    writer: &mut impl std::io::Write,
+) -> Result<(), std::io::Error> {
+    let segments: Vec<String> = widths.iter().map(|w| "─".repeat(w + 2)).collect();
+    let border = format!("{start}{}{end}", segments.join(&middle.to_string()));
+
+    ctx.theme
+        .table_border
+        .write_with_depth(|w| write!(w, "{border}"), writer, ctx.colors())?;
+
+    writeln!(writer)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::themes::get_dark_theme;
+    use crate::writer::write;
+    use colored::Colorize;
+
+    fn render(input: &str, is_writer_tty: bool) -> String {
+        let theme = get_dark_theme();
+        let mut result = Vec::new();
+        write(input, &theme, &mut result, is_writer_tty).unwrap();
+
+        String::from_utf8(result).unwrap()
+    }
+
+    #[test]
+    fn should_draw_borders_around_cells() {
+        let input = r#"| Name | Age |
+| ---- | --- |
+| Bob  | 4   |"#;
+
+        let expected = r#"
+┌──────┬─────┐
+│ Name │ Age │
+├──────┼─────┤
+│ Bob  │ 4   │
+└──────┴─────┘
+"#;
+
+        assert_eq!(render(input, false), expected);
+    }
+
+    #[test]
+    fn should_align_columns() {
+        let input = r#"| Left | Center | Right |
+| :--- | :----: | ----: |
+| a    | b      | c     |"#;
+
+        let expected = r#"
+┌──────┬────────┬───────┐
+│ Left │ Center │ Right │
+├──────┼────────┼───────┤
+│ a    │   b    │     c │
+└──────┴────────┴───────┘
+"#;
+
+        assert_eq!(render(input, false), expected);
+    }
+
+    #[test]
+    fn should_size_columns_by_visible_width() {
+        let input = r#"| Name |
+| ---- |
+| **Robert** |"#;
+
+        let result = render(input, true);
+        let lines: Vec<&str> = result.lines().collect();
+
+        assert!(lines[5].contains("└────────┘"));
+        assert!(lines[4].contains(&format!(" {} ", "Robert".bold())));
+    }
+
+    #[test]
+    fn should_keep_inline_formatting_in_cells() {
+        let input = r#"| Code |
+| ---- |
+| `x` |"#;
+
+        let expected = r#"
+┌──────┐
+│ Code │
+├──────┤
+│  x   │
+└──────┘
+"#;
+
+        assert_eq!(render(input, false), expected);
+    }
+}
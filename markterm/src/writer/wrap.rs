@@ -0,0 +1,230 @@
+use super::ansi::{char_width, segments, Segment};
+
+const RESET: &str = "\u{1b}[0m";
+const CLOSE_LINK: &str = "\u{1b}]8;;\u{1b}\\";
+
+/// Wraps the text so that none of its lines are wider than the width.
+///
+/// Lines are broken on the spaces between words, and words that are wider than the
+/// width are split. Line breaks that are already in the text are kept. Styles and
+/// hyperlinks that are open at the end of a line are closed before the line break and
+/// opened again on the next line, so that the prefixes written in front of every line
+/// by block quotes and lists are not styled.
+pub fn wrap(text: &str, width: usize) -> String {
+    let mut wrapper = Wrapper {
+        output: String::with_capacity(text.len()),
+        width: width.max(1),
+        line_width: 0,
+        spaces: String::new(),
+        styles: Vec::new(),
+        link: None,
+    };
+
+    for (index, line) in text.split('\n').enumerate() {
+        if index > 0 {
+            wrapper.spaces.clear();
+            wrapper.break_line();
+        }
+
+        for word in words(line) {
+            wrapper.push(word);
+        }
+    }
+
+    wrapper.finish()
+}
+
+/// A run of text that is either all spaces or has no spaces in it.
+enum Word<'a> {
+    Spaces(&'a str),
+    Text(Vec<Segment<'a>>),
+}
+
+struct Wrapper<'a> {
+    output: String,
+    width: usize,
+    line_width: usize,
+
+    /// Spaces that have not been written yet, as they are dropped if the line is broken.
+    spaces: String,
+
+    /// The style escape sequences that have been written since the last reset.
+    styles: Vec<&'a str>,
+
+    /// The escape sequence that opened the current hyperlink.
+    link: Option<&'a str>,
+}
+
+impl<'a> Wrapper<'a> {
+    fn push(&mut self, word: Word<'a>) {
+        let segments = match word {
+            Word::Spaces(spaces) => {
+                self.spaces.push_str(spaces);
+                return;
+            }
+            Word::Text(segments) => segments,
+        };
+
+        let word_width: usize = segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Text(text) => text.chars().map(char_width).sum(),
+                Segment::Escape(_) => 0,
+            })
+            .sum();
+
+        // Escapes on their own never break the line, and the spaces before them are kept
+        // until the next word is written.
+        if word_width == 0 {
+            for segment in segments {
+                if let Segment::Escape(escape) = segment {
+                    self.push_escape(escape);
+                }
+            }
+
+            return;
+        }
+
+        let spaces_width = self.spaces.chars().count();
+        if self.line_width > 0 && self.line_width + spaces_width + word_width > self.width {
+            self.spaces.clear();
+            self.break_line();
+        } else {
+            self.output.push_str(&self.spaces);
+            self.line_width += spaces_width;
+            self.spaces.clear();
+        }
+
+        for segment in segments {
+            match segment {
+                Segment::Escape(escape) => self.push_escape(escape),
+                Segment::Text(text) => {
+                    for c in text.chars() {
+                        let width = char_width(c);
+                        if self.line_width > 0 && self.line_width + width > self.width {
+                            self.break_line();
+                        }
+
+                        self.output.push(c);
+                        self.line_width += width;
+                    }
+                }
+            }
+        }
+    }
+
+    fn push_escape(&mut self, escape: &'a str) {
+        self.output.push_str(escape);
+
+        if let Some(link) = escape.strip_prefix("\u{1b}]8;") {
+            // A hyperlink is closed with an empty URI. i.e. `ESC]8;;ESC\`
+            let is_closing = link
+                .split_once(';')
+                .is_none_or(|(_, uri)| uri.trim_end_matches(['\u{1b}', '\\', '\u{7}']).is_empty());
+            self.link = if is_closing { None } else { Some(escape) };
+        } else if escape.ends_with('m') {
+            if escape == RESET || escape == "\u{1b}[m" {
+                self.styles.clear();
+            } else {
+                self.styles.push(escape);
+            }
+        }
+    }
+
+    fn break_line(&mut self) {
+        if self.link.is_some() {
+            self.output.push_str(CLOSE_LINK);
+        }
+
+        if !self.styles.is_empty() {
+            self.output.push_str(RESET);
+        }
+
+        self.output.push('\n');
+        self.line_width = 0;
+
+        for style in &self.styles {
+            self.output.push_str(style);
+        }
+
+        if let Some(link) = self.link {
+            self.output.push_str(link);
+        }
+    }
+
+    fn finish(mut self) -> String {
+        if self.line_width + self.spaces.chars().count() <= self.width {
+            self.output.push_str(&self.spaces);
+        }
+
+        self.output
+    }
+}
+
+/// Splits a line into words and the spaces between them.
+/// Escape sequences stay with the word they are next to.
+fn words(line: &str) -> Vec<Word<'_>> {
+    let mut words = Vec::new();
+    let mut current = Vec::new();
+
+    for segment in segments(line) {
+        let Segment::Text(mut text) = segment else {
+            current.push(segment);
+            continue;
+        };
+
+        while !text.is_empty() {
+            let spaces = text.len() - text.trim_start_matches(' ').len();
+            if spaces > 0 {
+                if !current.is_empty() {
+                    words.push(Word::Text(std::mem::take(&mut current)));
+                }
+
+                words.push(Word::Spaces(&text[..spaces]));
+                text = &text[spaces..];
+            } else {
+                let end = text.find(' ').unwrap_or(text.len());
+                current.push(Segment::Text(&text[..end]));
+                text = &text[end..];
+            }
+        }
+    }
+
+    if !current.is_empty() {
+        words.push(Word::Text(current));
+    }
+
+    words
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    macro_rules! wrap_test {
+        ($($name:ident: $value:expr,)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    let (input, width, expected) = $value;
+                    assert_eq!(wrap(input, width), expected);
+                }
+            )*
+        }
+    }
+
+    wrap_test! {
+        should_not_wrap_short_lines: ("Hello world", 20, "Hello world"),
+        should_wrap_on_spaces: ("Hello big world", 10, "Hello big\nworld"),
+        should_keep_existing_line_breaks: ("Hello\nworld", 20, "Hello\nworld"),
+        should_split_long_words: ("abcdefghij", 4, "abcd\nefgh\nij"),
+        should_count_wide_characters: ("日本 日本", 5, "日本\n日本"),
+        should_close_and_reopen_styles: ("\u{1b}[1mHello world\u{1b}[0m", 5, "\u{1b}[1mHello\u{1b}[0m\n\u{1b}[1mworld\u{1b}[0m"),
+        should_not_count_escapes: ("\u{1b}[1mHello\u{1b}[0m world", 11, "\u{1b}[1mHello\u{1b}[0m world"),
+        should_close_and_reopen_links: (
+            "\u{1b}]8;;http://a.b\u{1b}\\Hello world\u{1b}]8;;\u{1b}\\",
+            5,
+            "\u{1b}]8;;http://a.b\u{1b}\\Hello\u{1b}]8;;\u{1b}\\\n\u{1b}]8;;http://a.b\u{1b}\\world\u{1b}]8;;\u{1b}\\"
+        ),
+    }
+}